@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use sudoku_rs::SudokuBoard;
+
+// One 81-character puzzle per line, each with a unique solution
+const HARD_PUZZLES: &str = include_str!("../tests/fixtures/hard_puzzles.txt");
+
+fn hard_boards() -> Vec<SudokuBoard> {
+    HARD_PUZZLES
+        .lines()
+        .map(|line| SudokuBoard::from_str_grid(line).expect("Corpus puzzles are well formed"))
+        .collect()
+}
+
+// Each iteration solves the whole corpus, so the strategies are compared on the same work
+fn solve_hard_puzzles(c: &mut Criterion) {
+    let boards = hard_boards();
+    let mut group = c.benchmark_group("solve_hard_puzzles");
+    // A single pass over the corpus takes a good fraction of a second with the plain solver
+    group.sample_size(10);
+    group.bench_function("backtracking", |b| {
+        b.iter(|| {
+            for board in &boards {
+                let mut board = board.clone();
+                assert!(board.solve());
+            }
+        })
+    });
+    group.bench_function("fewest_candidates_first", |b| {
+        b.iter(|| {
+            for board in &boards {
+                let mut board = board.clone();
+                assert!(board.solve_with_heuristic());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, solve_hard_puzzles);
+criterion_main!(benches);
@@ -0,0 +1,482 @@
+//! Size-generic board for `N`x`N` grids with `sqrt(N)`-sized boxes: `Board<4>` for 4x4 minis,
+//! `Board<9>` for the classic game and `Board<16>` for hexadoku. Cells are addressed by 0-based
+//! row and column rather than the nested `CellCoordinate` of `SudokuBoard`, which only fits 9x9
+
+use std::collections::HashSet;
+
+use crate::{duplicates_in_unit, CellCoordinate, CellError, ParseError, SudokuBoard};
+
+// Side length of the boxes of an `n`x`n` grid, the build fails for sizes that aren't 1-16 and a
+// perfect square since the symbols only go up to 16
+const fn box_size(n: usize) -> usize {
+    let mut size = 1;
+    while size * size < n {
+        size += 1;
+    }
+    assert!(
+        size * size == n && n <= 16,
+        "board size must be a perfect square up to 16"
+    );
+    size
+}
+
+/// Character a value is written with: 1-9, then A-G for 10-16
+fn symbol(value: u8) -> char {
+    match value {
+        1..=9 => (b'0' + value) as char,
+        _ => (b'A' + value - 10) as char,
+    }
+}
+
+// Inverse of `symbol`, lowercase letters are accepted too
+fn value_of(character: char) -> Option<u8> {
+    match character {
+        '1'..='9' => Some(character as u8 - b'0'),
+        'A'..='G' => Some(character as u8 - b'A' + 10),
+        'a'..='g' => Some(character as u8 - b'a' + 10),
+        _ => None,
+    }
+}
+
+/// Outcome of `Board::make_move`, the counterpart of `SudokuMoveResult` with cells given as
+/// (row, column)
+#[derive(Debug, PartialEq)]
+pub enum BoardMoveResult {
+    Ok,
+    // The cells already holding the value plus the move's own cell, nothing is written
+    Invalid(Vec<(usize, usize)>),
+    Rejected(CellError),
+}
+
+/// 4x4 board with 2x2 boxes and digits 1-4, for quick games and fast solver tests. An alias
+/// rather than its own type: the size-generic `Board` already has the 4x4 `make_move` and
+/// validity checks, so a separate type would only duplicate them
+pub type MiniBoard = Board<4>;
+
+/// An `N`x`N` board holding 1-`N` in every row, column and box
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board<const N: usize> {
+    cells: [[Option<u8>; N]; N],
+    // Clues of the puzzle, these cells can't be edited
+    givens: [[bool; N]; N],
+}
+
+impl<const N: usize> Default for Board<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Board<N> {
+    const BOX_SIZE: usize = box_size(N);
+
+    /// Creates an empty board
+    pub fn new() -> Self {
+        // Evaluated here so an unsupported size fails the build as soon as a board is made
+        let _ = Self::BOX_SIZE;
+        Self {
+            cells: [[None; N]; N],
+            givens: [[false; N]; N],
+        }
+    }
+
+    /// Parses `N * N` characters read row-major, where 1-9 and A-G (10-16) are givens and `.`
+    /// or `0` are empty cells
+    pub fn from_str_grid(s: &str) -> Result<Self, ParseError> {
+        let nr_chars = s.chars().count();
+        if nr_chars != N * N {
+            return Err(ParseError::WrongLength {
+                expected: N * N,
+                found: nr_chars,
+            });
+        }
+        let mut board = Self::new();
+        for (index, character) in s.chars().enumerate() {
+            if character == '.' || character == '0' {
+                continue;
+            }
+            match value_of(character) {
+                Some(value) if value as usize <= N => {
+                    // The value was just checked to be in range and the cell is still empty
+                    let _ = board.set_given(index / N, index % N, value);
+                }
+                _ => {
+                    return Err(ParseError::InvalidCharacter {
+                        index,
+                        character,
+                        max_value: N as u8,
+                    })
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /// Serializes the board row-major with the symbols `from_str_grid` reads, `.` for empty cells
+    pub fn to_str_grid(&self) -> String {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|value| value.map_or('.', symbol))
+            .collect()
+    }
+
+    /// The value at the 0-based row and column, None for an empty or out of range cell
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        *self.cells.get(row)?.get(col)?
+    }
+
+    pub fn is_given(&self, row: usize, col: usize) -> bool {
+        self.givens
+            .get(row)
+            .and_then(|givens| givens.get(col))
+            .is_some_and(|given| *given)
+    }
+
+    /// Writes 1-`N` into the cell, givens can't be overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the row or column is out of range
+    pub fn set(&mut self, row: usize, col: usize, value: u8) -> Result<(), CellError> {
+        if !(1..=N).contains(&(value as usize)) {
+            return Err(CellError::ValueOutOfRange(value));
+        }
+        if self.givens[row][col] {
+            return Err(CellError::GivenCell);
+        }
+        self.cells[row][col] = Some(value);
+        Ok(())
+    }
+
+    /// Writes a clue, the cell can't be edited or cleared afterwards
+    pub fn set_given(&mut self, row: usize, col: usize, value: u8) -> Result<(), CellError> {
+        self.set(row, col, value)?;
+        self.givens[row][col] = true;
+        Ok(())
+    }
+
+    /// Plays the value into the cell when it breaks no rule, otherwise the board is left as is
+    ///
+    /// # Panics
+    ///
+    /// Panics when the row or column is out of range
+    pub fn make_move(&mut self, row: usize, col: usize, value: u8) -> BoardMoveResult {
+        if !(1..=N).contains(&(value as usize)) {
+            return BoardMoveResult::Rejected(CellError::ValueOutOfRange(value));
+        }
+        if self.givens[row][col] {
+            return BoardMoveResult::Rejected(CellError::GivenCell);
+        }
+        let mut conflicts: Vec<(usize, usize)> = Self::peers(row, col)
+            .filter(|(peer_row, peer_col)| self.cells[*peer_row][*peer_col] == Some(value))
+            .collect();
+        if conflicts.is_empty() {
+            self.cells[row][col] = Some(value);
+            return BoardMoveResult::Ok;
+        }
+        conflicts.sort_unstable();
+        conflicts.dedup();
+        conflicts.push((row, col));
+        BoardMoveResult::Invalid(conflicts)
+    }
+
+    /// Empties the cell unless it's a given
+    ///
+    /// # Panics
+    ///
+    /// Panics when the row or column is out of range
+    pub fn clear(&mut self, row: usize, col: usize) -> Result<(), CellError> {
+        if self.givens[row][col] {
+            return Err(CellError::GivenCell);
+        }
+        self.cells[row][col] = None;
+        Ok(())
+    }
+
+    /// Every filled cell, as (row, column), that shares its value with another cell of its row,
+    /// column or box
+    pub fn conflicting_cells(&self) -> HashSet<(usize, usize)> {
+        Self::units()
+            .flat_map(|unit| {
+                duplicates_in_unit(unit.map(|(row, col)| ((row, col), self.cells[row][col])))
+            })
+            .map(|(cell, _)| cell)
+            .collect()
+    }
+
+    /// True when no filled cell repeats its value within its row, column or box
+    pub fn is_valid(&self) -> bool {
+        self.conflicting_cells().is_empty()
+    }
+
+    /// True only when every cell is filled and the board is valid
+    pub fn is_complete(&self) -> bool {
+        self.cells.iter().flatten().all(Option::is_some) && self.is_valid()
+    }
+
+    /// Fills every empty cell using recursive backtracking. Returns false and leaves the board
+    /// untouched when no complete valid solution exists
+    pub fn solve(&mut self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        let empty_cells = self.empty_cells();
+        self.solve_cells(&empty_cells)
+    }
+
+    /// Counts the complete valid fillings of the board without mutating it, stopping once
+    /// `limit` were found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        if limit == 0 || !self.is_valid() {
+            return 0;
+        }
+        let mut board = self.clone();
+        let empty_cells = board.empty_cells();
+        let mut nr_solutions = 0;
+        board.count_cells(&empty_cells, limit, &mut nr_solutions);
+        nr_solutions
+    }
+
+    fn solve_cells(&mut self, empty_cells: &[(usize, usize)]) -> bool {
+        let Some((&(row, col), remaining_cells)) = empty_cells.split_first() else {
+            return true;
+        };
+        for value in 1..=N as u8 {
+            if self.fits(row, col, value) {
+                self.cells[row][col] = Some(value);
+                if self.solve_cells(remaining_cells) {
+                    return true;
+                }
+            }
+        }
+        self.cells[row][col] = None;
+        false
+    }
+
+    fn count_cells(
+        &mut self,
+        empty_cells: &[(usize, usize)],
+        limit: usize,
+        nr_solutions: &mut usize,
+    ) {
+        let Some((&(row, col), remaining_cells)) = empty_cells.split_first() else {
+            *nr_solutions += 1;
+            return;
+        };
+        for value in 1..=N as u8 {
+            if self.fits(row, col, value) {
+                self.cells[row][col] = Some(value);
+                self.count_cells(remaining_cells, limit, nr_solutions);
+                if *nr_solutions >= limit {
+                    break;
+                }
+            }
+        }
+        self.cells[row][col] = None;
+    }
+
+    // Whether the value is missing from the cell's row, column and box, the cell itself aside
+    fn fits(&self, row: usize, col: usize, value: u8) -> bool {
+        Self::peers(row, col)
+            .all(|(peer_row, peer_col)| self.cells[peer_row][peer_col] != Some(value))
+    }
+
+    fn empty_cells(&self) -> Vec<(usize, usize)> {
+        (0..N * N)
+            .map(|index| (index / N, index % N))
+            .filter(|(row, col)| self.cells[*row][*col].is_none())
+            .collect()
+    }
+
+    // The cells of every row, then every column, then every box
+    fn units() -> impl Iterator<Item = impl Iterator<Item = (usize, usize)>> {
+        (0..3 * N).map(|unit| {
+            let index = unit % N;
+            (0..N).map(move |offset| match unit / N {
+                0 => (index, offset),
+                1 => (offset, index),
+                _ => (
+                    index / Self::BOX_SIZE * Self::BOX_SIZE + offset / Self::BOX_SIZE,
+                    index % Self::BOX_SIZE * Self::BOX_SIZE + offset % Self::BOX_SIZE,
+                ),
+            })
+        })
+    }
+
+    // The cells sharing a row, column or box with the cell, a box peer in the same row or
+    // column is yielded twice
+    fn peers(row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (first_row, first_col) = (
+            row / Self::BOX_SIZE * Self::BOX_SIZE,
+            col / Self::BOX_SIZE * Self::BOX_SIZE,
+        );
+        let row_cells = (0..N).map(move |peer_col| (row, peer_col));
+        let column_cells = (0..N).map(move |peer_row| (peer_row, col));
+        let box_cells = (0..N).map(move |offset| {
+            (
+                first_row + offset / Self::BOX_SIZE,
+                first_col + offset % Self::BOX_SIZE,
+            )
+        });
+        row_cells
+            .chain(column_cells)
+            .chain(box_cells)
+            .filter(move |peer| *peer != (row, col))
+    }
+}
+
+/// The classic board as a `Board<9>`, givens included
+impl From<&SudokuBoard> for Board<9> {
+    fn from(sudoku_board: &SudokuBoard) -> Self {
+        let mut board = Board::new();
+        for (cell_coordinate, value) in sudoku_board.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            board.cells[row][col] = value;
+            board.givens[row][col] = sudoku_board.is_given(cell_coordinate);
+        }
+        board
+    }
+}
+
+/// The `Board<9>` as a classic board, givens included
+impl From<&Board<9>> for SudokuBoard {
+    fn from(board: &Board<9>) -> Self {
+        let mut sudoku_board = SudokuBoard::new();
+        for (row, values) in board.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                let (Some(value), Some(cell_coordinate)) =
+                    (value, CellCoordinate::from_rc(row, col))
+                else {
+                    continue;
+                };
+                // Values of a `Board<9>` are always 1-9
+                let _ = if board.givens[row][col] {
+                    sudoku_board.set_given(cell_coordinate, *value)
+                } else {
+                    sudoku_board.update_value(cell_coordinate, *value)
+                };
+            }
+        }
+        sudoku_board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import functions from the parent module
+    use crate::test_fixtures::PUZZLE_STR;
+
+    const MINI_PUZZLE: &str = "1.....2..3.....4";
+    const MINI_SOLUTION: &str = "1243342143122134";
+
+    #[test]
+    fn mini_board_is_parsed_validated_and_solved() {
+        let mut board =
+            Board::<4>::from_str_grid(MINI_PUZZLE).expect("Puzzle string is well formed");
+        assert!(board.is_given(0, 0));
+        assert!(board.is_valid());
+        assert!(!board.is_complete());
+        assert_eq!(1, board.count_solutions(2));
+
+        assert!(board.solve());
+        assert!(board.is_complete());
+        assert_eq!(MINI_SOLUTION, board.to_str_grid());
+    }
+
+    #[test]
+    fn mini_board_reports_box_conflict_and_rejects_edits() {
+        let mut board =
+            Board::<4>::from_str_grid(MINI_PUZZLE).expect("Puzzle string is well formed");
+        assert_eq!(Ok(()), board.set(1, 1, 1));
+        assert_eq!(HashSet::from([(0, 0), (1, 1)]), board.conflicting_cells());
+        assert!(!board.solve());
+        assert_eq!(Some(1), board.get(1, 1));
+
+        assert_eq!(Err(CellError::GivenCell), board.set(0, 0, 2));
+        assert_eq!(Err(CellError::ValueOutOfRange(5)), board.set(0, 1, 5));
+        assert_eq!(
+            Err(ParseError::InvalidCharacter {
+                index: 1,
+                character: '5',
+                max_value: 4
+            }),
+            Board::<4>::from_str_grid("15..............")
+        );
+        assert_eq!(
+            Err(ParseError::WrongLength {
+                expected: 16,
+                found: 15
+            }),
+            Board::<4>::from_str_grid(&MINI_PUZZLE[1..])
+        );
+    }
+
+    #[test]
+    fn complete_mini_board_is_valid() {
+        let board =
+            MiniBoard::from_str_grid(MINI_SOLUTION).expect("Solution string is well formed");
+        assert!(board.is_valid());
+        assert!(board.is_complete());
+        assert!(board.conflicting_cells().is_empty());
+    }
+
+    #[test]
+    fn mini_board_move_conflicting_in_row_and_box_is_refused() {
+        let mut board =
+            MiniBoard::from_str_grid(MINI_PUZZLE).expect("Puzzle string is well formed");
+        assert_eq!(BoardMoveResult::Ok, board.make_move(0, 1, 2));
+        assert_eq!(Some(2), board.get(0, 1));
+
+        assert_eq!(
+            BoardMoveResult::Invalid(vec![(0, 1), (1, 2), (1, 0)]),
+            board.make_move(1, 0, 2)
+        );
+        assert_eq!(None, board.get(1, 0));
+        assert_eq!(
+            BoardMoveResult::Rejected(CellError::GivenCell),
+            board.make_move(0, 0, 4)
+        );
+        assert_eq!(
+            BoardMoveResult::Rejected(CellError::ValueOutOfRange(5)),
+            board.make_move(1, 0, 5)
+        );
+
+        let conflicting_board =
+            MiniBoard::from_str_grid("12..2...........").expect("Puzzle string is well formed");
+        assert_eq!(
+            HashSet::from([(0, 1), (1, 0)]),
+            conflicting_board.conflicting_cells()
+        );
+        assert!(!conflicting_board.is_valid());
+    }
+
+    #[test]
+    fn classic_board_solves_like_sudoku_board() {
+        let mut sudoku_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let mut board = Board::<9>::from(&sudoku_board);
+        assert_eq!(PUZZLE_STR, board.to_str_grid());
+        assert!(board.solve());
+        assert!(sudoku_board.solve());
+        assert_eq!(sudoku_board.to_str_grid(), board.to_str_grid());
+
+        let round_tripped_board = SudokuBoard::from(
+            &Board::<9>::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
+        );
+        assert_eq!(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
+            round_tripped_board
+        );
+    }
+
+    #[test]
+    fn hexadoku_symbols_go_up_to_g() {
+        let mut puzzle = "G".to_string();
+        puzzle.push_str(&".".repeat(255));
+        let board = Board::<16>::from_str_grid(&puzzle).expect("Puzzle string is well formed");
+        assert_eq!(Some(16), board.get(0, 0));
+        assert_eq!(puzzle, board.to_str_grid());
+        assert_eq!(None, board.get(16, 0));
+    }
+}
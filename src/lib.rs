@@ -0,0 +1,3828 @@
+//! Board, solver and generator of the Sudoku game, the egui front end lives in `main.rs`
+//!
+//! The intended public surface is re-exported from [`prelude`]: build a [`SudokuBoard`], address
+//! its cells with [`CellCoordinate`] (or the [`PositionId`], [`Row`] and [`Column`] pair it is
+//! made of), play [`SudokuMove`]s and inspect the [`SudokuMoveResult`], [`CellError`] and
+//! [`ParseError`] they produce. [`SubGrid`] is public but only needed to work on a single 3x3 block.
+//! Grids of other sizes, such as 4x4 or 16x16, are played on the size-generic [`Board`]
+
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+mod board;
+#[cfg(test)]
+mod test_fixtures;
+
+pub use board::{Board, BoardMoveResult, MiniBoard};
+
+/// Everything needed to construct and query a board, `use sudoku_rs::prelude::*;`
+pub mod prelude {
+    pub use crate::{
+        Board, BoardMoveResult, BoardStats, CellCoordinate, CellError, Column, Difficulty,
+        FileError, FlatBoard, LogicalStep, MiniBoard, ParseError, PositionId, PuzzleError, Row,
+        SudokuBoard, SudokuMove, SudokuMoveResult, Technique, UnitId, Variant,
+    };
+}
+
+// Every cell of a row, column or sub-grid whose value appears more than once in it, paired
+// with that value. Shared by all duplicate checks so the three unit types agree, and by the
+// size-generic `Board` whose values go up to 16
+fn duplicates_in_unit<K: Copy>(cells: impl IntoIterator<Item = (K, Option<u8>)>) -> Vec<(K, u8)> {
+    let filled_cells: Vec<(K, u8)> = cells
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect();
+    let mut value_counts = [0_u8; 17];
+    for (_, value) in &filled_cells {
+        value_counts[*value as usize] += 1;
+    }
+    filled_cells
+        .into_iter()
+        .filter(|(_, value)| value_counts[*value as usize] > 1)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Row {
+    Upper = 1,
+    Center = 2,
+    Bottom = 3,
+}
+
+impl Row {
+    pub fn all() -> &'static [Row] {
+        &[Row::Upper, Row::Center, Row::Bottom]
+    }
+
+    /// The row with the 1-3 discriminant, None for any other index
+    pub fn from_index(index: u8) -> Option<Row> {
+        match index {
+            1 => Some(Row::Upper),
+            2 => Some(Row::Center),
+            3 => Some(Row::Bottom),
+            _ => None,
+        }
+    }
+
+    /// The 1-3 discriminant, top to bottom
+    pub fn to_index(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Column {
+    Left = 1,
+    Center = 2,
+    Right = 3,
+}
+
+impl Column {
+    pub fn all() -> &'static [Column] {
+        &[Column::Left, Column::Center, Column::Right]
+    }
+
+    /// The column with the 1-3 discriminant, None for any other index
+    pub fn from_index(index: u8) -> Option<Column> {
+        match index {
+            1 => Some(Column::Left),
+            2 => Some(Column::Center),
+            3 => Some(Column::Right),
+            _ => None,
+        }
+    }
+
+    /// The 1-3 discriminant, left to right
+    pub fn to_index(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Serialized as a `R<row>C<column>` string with 1-3 indices, e.g. `"R1C2"` for the upper
+/// center position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PositionId {
+    pub row: Row,
+    pub column: Column,
+}
+
+impl fmt::Display for PositionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "R{}C{}", self.row.to_index(), self.column.to_index())
+    }
+}
+
+impl Serialize for PositionId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// Saves written before the string form stored positions as a struct of the two enums
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedPositionId {
+    Key(String),
+    Fields { row: Row, column: Column },
+}
+
+impl<'de> Deserialize<'de> for PositionId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match SerializedPositionId::deserialize(deserializer)? {
+            SerializedPositionId::Key(key) => PositionId::from_key(&key).ok_or_else(|| {
+                de::Error::custom(format!("invalid position {:?}, expected R1C1-R3C3", key))
+            }),
+            SerializedPositionId::Fields { row, column } => Ok(PositionId { row, column }),
+        }
+    }
+}
+
+impl PositionId {
+    // Inverse of the `Display` form
+    fn from_key(key: &str) -> Option<PositionId> {
+        let [b'R', row, b'C', column] = *key.as_bytes() else {
+            return None;
+        };
+        Some(PositionId {
+            row: Row::from_index(row.checked_sub(b'0')?)?,
+            column: Column::from_index(column.checked_sub(b'0')?)?,
+        })
+    }
+
+    // Zero-based (row, column) of the position inside its 3x3 array
+    fn indices(self) -> (usize, usize) {
+        (
+            self.row.to_index() as usize - 1,
+            self.column.to_index() as usize - 1,
+        )
+    }
+}
+
+/// Represents a 3x3 section of the Sudoku board
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubGrid {
+    // Indexed by `PositionId::indices`, the solver reads these millions of times
+    cells: [[Option<u8>; 3]; 3],
+    // Clues of the puzzle, these cells can't be edited
+    givens: [[bool; 3]; 3],
+    // Pencil marks of each cell
+    candidates: [[HashSet<u8>; 3]; 3],
+}
+
+pub struct SubGridMove {
+    pub cell: PositionId,
+    pub value: u8,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SubgridMoveResult {
+    Ok,
+    Invalid(Vec<PositionId>),
+    Rejected(CellError),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CellError {
+    // Carries the rejected value, only 1-9 can be written
+    ValueOutOfRange(u8),
+    GivenCell,
+}
+
+impl SubGrid {
+    /// Creates a new empty 3x3 subgrid
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empties every cell, including givens, and drops all pencil marks
+    pub fn clear(&mut self) {
+        self.cells = Default::default();
+        self.givens = Default::default();
+        for cell_candidates in self.candidates.iter_mut().flatten() {
+            cell_candidates.clear();
+        }
+    }
+
+    pub fn update_value(&mut self, key: PositionId, value: u8) -> Result<(), CellError> {
+        self.check_writable(key, value)?;
+        let (row, col) = key.indices();
+        self.cells[row][col] = Some(value);
+        self.candidates[row][col].clear();
+        Ok(())
+    }
+
+    pub fn clear_value(&mut self, key: PositionId) -> Result<(), CellError> {
+        if self.is_given(key) {
+            return Err(CellError::GivenCell);
+        }
+        let (row, col) = key.indices();
+        self.cells[row][col] = None;
+        Ok(())
+    }
+
+    /// Writes a clue, the cell can't be edited or cleared afterwards
+    pub fn set_given(&mut self, key: PositionId, value: u8) -> Result<(), CellError> {
+        self.update_value(key, value)?;
+        let (row, col) = key.indices();
+        self.givens[row][col] = true;
+        Ok(())
+    }
+
+    pub fn is_given(&self, key: PositionId) -> bool {
+        let (row, col) = key.indices();
+        self.givens[row][col]
+    }
+
+    /// Turns every filled cell into a clue, empty cells stay editable
+    pub fn lock_as_givens(&mut self) {
+        for (given, value) in self
+            .givens
+            .iter_mut()
+            .flatten()
+            .zip(self.cells.iter().flatten())
+        {
+            *given |= value.is_some();
+        }
+    }
+
+    /// Adds the candidate to the cell's pencil marks, or removes it if it was already there
+    pub fn toggle_candidate(&mut self, key: PositionId, value: u8) -> Result<(), CellError> {
+        if !(1..=9).contains(&value) {
+            return Err(CellError::ValueOutOfRange(value));
+        }
+        let (row, col) = key.indices();
+        let cell_candidates = &mut self.candidates[row][col];
+        if !cell_candidates.remove(&value) {
+            cell_candidates.insert(value);
+        }
+        Ok(())
+    }
+
+    pub fn clear_candidates(&mut self, key: PositionId) {
+        let (row, col) = key.indices();
+        self.candidates[row][col].clear();
+    }
+
+    pub fn get_candidates(&self, key: PositionId) -> HashSet<u8> {
+        let (row, col) = key.indices();
+        self.candidates[row][col].clone()
+    }
+
+    pub fn get_value(&self, key: PositionId) -> Option<u8> {
+        let (row, col) = key.indices();
+        self.cells[row][col]
+    }
+
+    pub fn contains_value(&self, value: u8) -> bool {
+        self.cells
+            .iter()
+            .flatten()
+            .any(|cell_value| *cell_value == Some(value))
+    }
+
+    // Returns vec with all the positions where there is a duplicate
+    pub fn get_duplicates(&self) -> Option<Vec<PositionId>> {
+        let cells = Row::all().iter().flat_map(|row| {
+            Column::all().iter().map(move |column| {
+                let position_id = PositionId {
+                    row: *row,
+                    column: *column,
+                };
+                (position_id, self.get_value(position_id))
+            })
+        });
+        let duplicates: Vec<PositionId> = duplicates_in_unit(cells)
+            .into_iter()
+            .map(|(position_id, _)| position_id)
+            .collect();
+        if !duplicates.is_empty() {
+            Some(duplicates)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the value unless it repeats one already in the sub-grid. An invalid move leaves the
+    /// sub-grid untouched and reports the cell holding the value along with the move's cell
+    pub fn make_move(&mut self, sub_grid_move: SubGridMove) -> SubgridMoveResult {
+        if let Err(cell_error) = self.check_writable(sub_grid_move.cell, sub_grid_move.value) {
+            return SubgridMoveResult::Rejected(cell_error);
+        }
+
+        let mut invalid_cells: Vec<PositionId> = Row::all()
+            .iter()
+            .flat_map(|row| {
+                Column::all().iter().map(move |column| PositionId {
+                    row: *row,
+                    column: *column,
+                })
+            })
+            .filter(|position_id| {
+                *position_id != sub_grid_move.cell
+                    && self.get_value(*position_id) == Some(sub_grid_move.value)
+            })
+            .collect();
+        if !invalid_cells.is_empty() {
+            invalid_cells.push(sub_grid_move.cell);
+            return SubgridMoveResult::Invalid(invalid_cells);
+        }
+
+        match self.update_value(sub_grid_move.cell, sub_grid_move.value) {
+            Ok(()) => SubgridMoveResult::Ok,
+            Err(cell_error) => SubgridMoveResult::Rejected(cell_error),
+        }
+    }
+
+    // The errors `update_value` would fail with, checked without writing
+    fn check_writable(&self, key: PositionId, value: u8) -> Result<(), CellError> {
+        if !(1..=9).contains(&value) {
+            return Err(CellError::ValueOutOfRange(value));
+        }
+        if self.is_given(key) {
+            return Err(CellError::GivenCell);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CellCoordinate {
+    pub sub_grid: PositionId,
+    pub cell: PositionId,
+}
+
+impl CellCoordinate {
+    /// Maps 0-8 row/column indices over the 9x9 grid onto the sub-grid and cell pair.
+    /// Returns None when either index is out of range
+    pub fn from_rc(row: usize, col: usize) -> Option<CellCoordinate> {
+        if row >= 9 || col >= 9 {
+            return None;
+        }
+        Some(CellCoordinate {
+            sub_grid: PositionId {
+                row: Row::all()[row / 3],
+                column: Column::all()[col / 3],
+            },
+            cell: PositionId {
+                row: Row::all()[row % 3],
+                column: Column::all()[col % 3],
+            },
+        })
+    }
+
+    /// Inverse of `from_rc`
+    pub fn to_rc(self) -> (usize, usize) {
+        let (sub_grid_row, sub_grid_col) = self.sub_grid.indices();
+        let (cell_row, cell_col) = self.cell.indices();
+        (sub_grid_row * 3 + cell_row, sub_grid_col * 3 + cell_col)
+    }
+
+    // Same mapping using a 0-80 row-major index over the whole grid
+    pub fn from_grid_index(index: usize) -> Option<CellCoordinate> {
+        Self::from_rc(index / 9, index % 9)
+    }
+
+    /// Index 0-8 of the sub-grid the cell is in, counted row-major like `SudokuBoard::box_cells`
+    pub fn box_index(self) -> usize {
+        let (row, col) = self.sub_grid.indices();
+        row * 3 + col
+    }
+
+    pub fn to_grid_index(self) -> usize {
+        let (row, col) = self.to_rc();
+        row * 9 + col
+    }
+}
+
+/// One of the units a digit may appear only once in. Indices are 0-8, rows top to bottom,
+/// columns left to right and boxes row-major. Diagonals only count under `Variant::XSudoku`,
+/// 0 runs from the top left corner and 1 from the top right one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitId {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+    Diagonal(usize),
+}
+
+impl UnitId {
+    /// The nine cells of the unit, none for an index out of range
+    pub fn cells(self) -> Vec<CellCoordinate> {
+        match self {
+            UnitId::Row(row) => SudokuBoard::row_coordinates(row).collect(),
+            UnitId::Column(col) => SudokuBoard::column_coordinates(col).collect(),
+            UnitId::Box(box_index) => SudokuBoard::box_coordinates(box_index).collect(),
+            UnitId::Diagonal(diagonal) => SudokuBoard::diagonal_coordinates(diagonal).collect(),
+        }
+    }
+}
+
+/// Ruleset a board is checked and solved under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Classic,
+    // Both main diagonals must also hold 1-9 without repeating
+    XSudoku,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    // `max_value` is the largest value the board holds, so the message can list what's allowed
+    InvalidCharacter {
+        index: usize,
+        character: char,
+        max_value: u8,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::WrongLength { expected, found } => {
+                write!(f, "expected {} cells, found {}", expected, found)
+            }
+            ParseError::InvalidCharacter {
+                index,
+                character,
+                max_value,
+            } => {
+                // Values above 9 are written as letters, A being 10
+                let values = if *max_value > 9 {
+                    format!("1-9, A-{}", (b'A' + max_value - 10) as char)
+                } else {
+                    format!("1-{}", max_value)
+                };
+                write!(
+                    f,
+                    "invalid character {:?} at position {}, only {}, . and 0 are allowed",
+                    character,
+                    index + 1,
+                    values
+                )
+            }
+        }
+    }
+}
+
+/// Why a parsed board can't be played as a puzzle, see `SudokuBoard::validate_puzzle`
+#[derive(Debug, PartialEq)]
+pub enum PuzzleError {
+    // Carries every cell that shares its digit with another one of its units
+    InitialConflict(Vec<CellCoordinate>),
+    Unsolvable,
+    NotUnique,
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::InitialConflict(cells) => {
+                write!(f, "{} clues repeat a digit in their unit", cells.len())
+            }
+            PuzzleError::Unsolvable => write!(f, "the puzzle has no solution"),
+            PuzzleError::NotUnique => write!(f, "the puzzle has more than one solution"),
+        }
+    }
+}
+
+/// Failure to read a `.sdk` file or a CSV grid, lines are numbered from 0
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    WrongLineCount(usize),
+    Line { line: usize, error: ParseError },
+}
+
+impl From<io::Error> for FileError {
+    fn from(error: io::Error) -> Self {
+        FileError::Io(error)
+    }
+}
+
+// Clues left on the board by the generator for each difficulty. Clue removal stops early when
+// no further cell can be emptied without losing uniqueness, so these are lower bounds
+pub const EASY_GIVENS: usize = 40;
+pub const MEDIUM_GIVENS: usize = 32;
+pub const HARD_GIVENS: usize = 28;
+pub const EXPERT_GIVENS: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn givens(&self) -> usize {
+        match self {
+            Difficulty::Easy => EASY_GIVENS,
+            Difficulty::Medium => MEDIUM_GIVENS,
+            Difficulty::Hard => HARD_GIVENS,
+            Difficulty::Expert => EXPERT_GIVENS,
+        }
+    }
+}
+
+/// Represents the full 9x9 Sudoku board
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SudokuBoard {
+    // Indexed by `PositionId::indices` of the sub-grid
+    sub_grids: [[SubGrid; 3]; 3],
+    // When set, moves that break no rule but differ from it are reported as wrong
+    #[serde(default)]
+    reference_solution: Option<Box<SudokuBoard>>,
+    #[serde(default)]
+    variant: Variant,
+}
+
+/// Counts describing a board at one point in time, see `SudokuBoard::stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardStats {
+    pub filled_cells: usize,
+    pub empty_cells: usize,
+    // How often each digit is placed, index 0 is digit 1
+    pub digit_counts: [usize; 9],
+    pub is_valid: bool,
+    pub is_complete: bool,
+}
+
+/// The 81 cell values in reading order, for code that doesn't care about the sub-grid layout.
+/// Givens, pencil marks and the variant aren't part of it. Serializes as a list of 81 digits or
+/// nulls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlatBoard(pub [Option<u8>; 81]);
+
+/// Deductions `SudokuBoard::next_logical_step` looks for, from easiest to hardest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Technique {
+    // The cell has a single legal candidate
+    NakedSingle,
+    // The value fits in a single cell of one of the cell's units
+    HiddenSingle,
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Technique::NakedSingle => write!(f, "naked single"),
+            Technique::HiddenSingle => write!(f, "hidden single"),
+        }
+    }
+}
+
+/// A value that can be placed by deduction alone, see `SudokuBoard::next_logical_step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogicalStep {
+    pub cell_coordinate: CellCoordinate,
+    pub value: u8,
+    pub technique: Technique,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SudokuMove {
+    pub cell_coordinate: CellCoordinate,
+    pub value: u8,
+}
+
+#[derive(Debug)]
+pub enum SudokuMoveResult {
+    // The value now in the cell, so callers can log or record it without the original move
+    Ok {
+        cell_coordinate: CellCoordinate,
+        value: u8,
+    },
+    Invalid(Vec<CellCoordinate>),
+    Rejected(CellError),
+    // Breaks no rule but differs from the reference solution, the value is still written
+    WrongValue(CellCoordinate),
+}
+
+impl SudokuBoard {
+    /// Creates an empty Sudoku board
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty board played under the variant's rules
+    pub fn with_variant(variant: Variant) -> Self {
+        Self {
+            variant,
+            ..Self::default()
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Empties every cell in place, givens and pencil marks included. The variant is kept
+    pub fn clear(&mut self) {
+        for sub_grid in self.sub_grids.iter_mut().flatten() {
+            sub_grid.clear();
+        }
+        self.reference_solution = None;
+    }
+
+    /// Sets the solution moves are compared with for the stricter `WrongValue` result, None
+    /// turns the comparison off
+    pub fn set_reference_solution(&mut self, solution: Option<SudokuBoard>) {
+        self.reference_solution = solution.map(Box::new);
+    }
+
+    fn sub_grid(&self, position_id: PositionId) -> &SubGrid {
+        let (row, col) = position_id.indices();
+        &self.sub_grids[row][col]
+    }
+
+    fn sub_grid_mut(&mut self, position_id: PositionId) -> &mut SubGrid {
+        let (row, col) = position_id.indices();
+        &mut self.sub_grids[row][col]
+    }
+
+    /// Builds a board from nine rows of nine values, where 0 is an empty cell. The values are
+    /// plain entries rather than givens. Fails with `ValueOutOfRange` on a value above 9
+    pub fn from_array(grid: [[u8; 9]; 9]) -> Result<SudokuBoard, CellError> {
+        let mut board = SudokuBoard::new();
+        for (row, values) in grid.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if let (1.., Some(cell_coordinate)) = (*value, CellCoordinate::from_rc(row, col)) {
+                    board.update_value(cell_coordinate, *value)?;
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /// Exports the board as nine rows of nine values, the inverse of `from_array`: 0 is an empty
+    /// cell. Givens and pencil marks aren't part of it
+    pub fn to_array(&self) -> [[u8; 9]; 9] {
+        let mut grid = [[0_u8; 9]; 9];
+        for (cell_coordinate, value) in self.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            grid[row][col] = value.unwrap_or(0);
+        }
+        grid
+    }
+
+    /// Parses the common 81-character format, read row-major, where digits 1-9 are givens and
+    /// `.` or `0` are empty cells. The digits are stored as givens
+    pub fn from_str_grid(s: &str) -> Result<SudokuBoard, ParseError> {
+        let nr_chars = s.chars().count();
+        if nr_chars != 81 {
+            return Err(ParseError::WrongLength {
+                expected: 81,
+                found: nr_chars,
+            });
+        }
+
+        let mut board = SudokuBoard::new();
+        for (index, character) in s.chars().enumerate() {
+            match character {
+                '.' | '0' => {}
+                '1'..='9' => {
+                    let value = character as u8 - b'0';
+                    // The length check keeps the index in range and set_given accepts 1-9
+                    if let Some(cell_coordinate) = CellCoordinate::from_grid_index(index) {
+                        let _ = board.set_given(cell_coordinate, value);
+                    }
+                }
+                _ => {
+                    return Err(ParseError::InvalidCharacter {
+                        index,
+                        character,
+                        max_value: 9,
+                    })
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /// Serializes the board to the 81-character row-major format, using `.` for empty cells
+    pub fn to_str_grid(&self) -> String {
+        self.iter_cells()
+            .map(|(_, value)| value.map_or('.', |value| (b'0' + value) as char))
+            .collect()
+    }
+
+    /// The cells of the 0-8 row, left to right. Yields nothing for an out of range row
+    pub fn row_cells(&self, row: usize) -> impl Iterator<Item = (CellCoordinate, Option<u8>)> + '_ {
+        Self::row_coordinates(row)
+            .map(|cell_coordinate| (cell_coordinate, self.get_value(cell_coordinate)))
+    }
+
+    /// The cells of the 0-8 column, top to bottom. Yields nothing for an out of range column
+    pub fn column_cells(
+        &self,
+        col: usize,
+    ) -> impl Iterator<Item = (CellCoordinate, Option<u8>)> + '_ {
+        Self::column_coordinates(col)
+            .map(|cell_coordinate| (cell_coordinate, self.get_value(cell_coordinate)))
+    }
+
+    /// The cells of the 0-8 box, boxes and the cells inside them both counted row-major. Yields
+    /// nothing for an out of range box
+    pub fn box_cells(
+        &self,
+        box_index: usize,
+    ) -> impl Iterator<Item = (CellCoordinate, Option<u8>)> + '_ {
+        Self::box_coordinates(box_index)
+            .map(|cell_coordinate| (cell_coordinate, self.get_value(cell_coordinate)))
+    }
+
+    /// Reads a `.sdk` file: nine lines of nine characters, each line parsed like `from_str_grid`.
+    /// Trailing blank lines are allowed
+    pub fn from_file(path: impl AsRef<Path>) -> Result<SudokuBoard, FileError> {
+        let contents = fs::read_to_string(path)?;
+        let lines: Vec<&str> = contents.trim_end().lines().map(str::trim_end).collect();
+        if lines.len() != 9 {
+            return Err(FileError::WrongLineCount(lines.len()));
+        }
+        for (line, text) in lines.iter().enumerate() {
+            let nr_chars = text.chars().count();
+            if nr_chars != 9 {
+                return Err(FileError::Line {
+                    line,
+                    error: ParseError::WrongLength {
+                        expected: 9,
+                        found: nr_chars,
+                    },
+                });
+            }
+        }
+        Self::from_rows(&lines)
+    }
+
+    /// Parses nine lines of nine comma-separated fields, as exported by spreadsheets. Fields hold
+    /// a digit 1-9 or are blank for an empty cell, surrounding spaces are ignored. A field with
+    /// more than one character is reported at its second character. Trailing blank lines are
+    /// allowed
+    pub fn from_csv(s: &str) -> Result<SudokuBoard, FileError> {
+        let lines: Vec<&str> = s.trim_end().lines().collect();
+        if lines.len() != 9 {
+            return Err(FileError::WrongLineCount(lines.len()));
+        }
+        let mut rows = Vec::with_capacity(9);
+        for (line, text) in lines.iter().enumerate() {
+            let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+            if fields.len() != 9 {
+                return Err(FileError::Line {
+                    line,
+                    error: ParseError::WrongLength {
+                        expected: 9,
+                        found: fields.len(),
+                    },
+                });
+            }
+            let mut row = String::with_capacity(9);
+            for (index, field) in fields.iter().enumerate() {
+                let mut characters = field.chars();
+                match (characters.next(), characters.next()) {
+                    (None, _) => row.push('.'),
+                    (Some(character), None) => row.push(character),
+                    (Some(_), Some(character)) => {
+                        return Err(FileError::Line {
+                            line,
+                            error: ParseError::InvalidCharacter {
+                                index,
+                                character,
+                                max_value: 9,
+                            },
+                        })
+                    }
+                }
+            }
+            rows.push(row);
+        }
+        Self::from_rows(&rows)
+    }
+
+    // Parses nine rows already checked to hold nine characters each, errors point at the line
+    fn from_rows(rows: &[impl AsRef<str>]) -> Result<SudokuBoard, FileError> {
+        let grid: String = rows.iter().map(AsRef::as_ref).collect();
+        Self::from_str_grid(&grid).map_err(|parse_error| match parse_error {
+            ParseError::InvalidCharacter {
+                index,
+                character,
+                max_value,
+            } => FileError::Line {
+                line: index / 9,
+                error: ParseError::InvalidCharacter {
+                    index: index % 9,
+                    character,
+                    max_value,
+                },
+            },
+            // Every line was checked to hold nine characters
+            ParseError::WrongLength { .. } => FileError::WrongLineCount(rows.len()),
+        })
+    }
+
+    /// Writes the board as a `.sdk` file, nine lines of nine characters with `.` for empty cells
+    pub fn to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let str_grid = self.to_str_grid();
+        let mut contents = String::with_capacity(90);
+        for row in 0..9 {
+            contents.push_str(&str_grid[row * 9..(row + 1) * 9]);
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+
+    /// Iterates over all 81 cells in row-major order, row 0 first and column 0 first within it
+    pub fn iter_cells(&self) -> impl Iterator<Item = (CellCoordinate, Option<u8>)> + '_ {
+        (0..81)
+            .filter_map(CellCoordinate::from_grid_index)
+            .map(|cell_coordinate| (cell_coordinate, self.get_value(cell_coordinate)))
+    }
+
+    /// Iterates over the nine sub-grids in row-major order, the upper left one first
+    pub fn sub_grids_ordered(&self) -> impl Iterator<Item = (PositionId, &SubGrid)> + '_ {
+        Row::all().iter().flat_map(move |row| {
+            Column::all().iter().map(move |column| {
+                let position_id = PositionId {
+                    row: *row,
+                    column: *column,
+                };
+                (position_id, self.sub_grid(position_id))
+            })
+        })
+    }
+
+    pub fn update_value(
+        &mut self,
+        cell_coordinate: CellCoordinate,
+        value: u8,
+    ) -> Result<(), CellError> {
+        self.sub_grid_mut(cell_coordinate.sub_grid)
+            .update_value(cell_coordinate.cell, value)
+    }
+
+    /// Empties the cell, duplicates are computed on demand so any conflict it was part of is gone
+    pub fn clear_value(&mut self, cell_coordinate: CellCoordinate) -> Result<(), CellError> {
+        self.sub_grid_mut(cell_coordinate.sub_grid)
+            .clear_value(cell_coordinate.cell)
+    }
+
+    /// Writes a clue of the puzzle, which can't be edited or cleared afterwards
+    pub fn set_given(
+        &mut self,
+        cell_coordinate: CellCoordinate,
+        value: u8,
+    ) -> Result<(), CellError> {
+        self.sub_grid_mut(cell_coordinate.sub_grid)
+            .set_given(cell_coordinate.cell, value)
+    }
+
+    pub fn is_given(&self, cell_coordinate: CellCoordinate) -> bool {
+        self.sub_grid(cell_coordinate.sub_grid)
+            .is_given(cell_coordinate.cell)
+    }
+
+    /// Freezes the board as it is: every filled cell becomes a given that can't be edited or
+    /// cleared, empty cells stay editable
+    pub fn lock_as_givens(&mut self) {
+        for sub_grid in self.sub_grids.iter_mut().flatten() {
+            sub_grid.lock_as_givens();
+        }
+    }
+
+    /// Copies the filled cells of `other` into the cells that are empty here, so a partial fill
+    /// such as a solver's can be applied without clobbering entries already on the board
+    pub fn overlay(&mut self, other: &SudokuBoard) {
+        for (cell_coordinate, value) in other.iter_cells() {
+            if let (Some(value), None) = (value, self.get_value(cell_coordinate)) {
+                // Empty cells are never givens and the value came from a board, so this can't fail
+                let _ = self.update_value(cell_coordinate, value);
+            }
+        }
+    }
+
+    pub fn toggle_candidate(
+        &mut self,
+        cell_coordinate: CellCoordinate,
+        value: u8,
+    ) -> Result<(), CellError> {
+        self.sub_grid_mut(cell_coordinate.sub_grid)
+            .toggle_candidate(cell_coordinate.cell, value)
+    }
+
+    /// Toggles the candidate across several empty cells at once: it's removed when all of them
+    /// already have it, otherwise it's added to the ones missing it. Filled cells are skipped
+    pub fn toggle_candidates(
+        &mut self,
+        cell_coordinates: impl IntoIterator<Item = CellCoordinate>,
+        value: u8,
+    ) -> Result<(), CellError> {
+        if !(1..=9).contains(&value) {
+            return Err(CellError::ValueOutOfRange(value));
+        }
+        let empty_cells: Vec<CellCoordinate> = cell_coordinates
+            .into_iter()
+            .filter(|cell_coordinate| self.get_value(*cell_coordinate).is_none())
+            .collect();
+        let all_marked = empty_cells
+            .iter()
+            .all(|cell_coordinate| self.get_candidates(*cell_coordinate).contains(&value));
+        for cell_coordinate in empty_cells {
+            if all_marked || !self.get_candidates(cell_coordinate).contains(&value) {
+                self.toggle_candidate(cell_coordinate, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the cell's value from the pencil marks of all its peers, see `peers`. Never adds a
+    /// candidate and does nothing for an empty cell
+    pub fn prune_candidates_after(&mut self, cell_coordinate: CellCoordinate) {
+        let Some(value) = self.get_value(cell_coordinate) else {
+            return;
+        };
+        for peer in self.peers(&cell_coordinate) {
+            if self.get_candidates(peer).contains(&value) {
+                // The value came from the board, so it's in range
+                let _ = self.toggle_candidate(peer, value);
+            }
+        }
+    }
+
+    pub fn clear_candidates(&mut self, cell_coordinate: CellCoordinate) {
+        self.sub_grid_mut(cell_coordinate.sub_grid)
+            .clear_candidates(cell_coordinate.cell);
+    }
+
+    pub fn get_candidates(&self, cell_coordinate: CellCoordinate) -> HashSet<u8> {
+        self.sub_grid(cell_coordinate.sub_grid)
+            .get_candidates(cell_coordinate.cell)
+    }
+
+    pub fn get_value(&self, cell_coordinate: CellCoordinate) -> Option<u8> {
+        self.sub_grid(cell_coordinate.sub_grid)
+            .get_value(cell_coordinate.cell)
+    }
+
+    /// Value at the 0-8 row and column.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the row or column is out of range
+    pub fn get_rc(&self, row: usize, col: usize) -> Option<u8> {
+        self.get_value(Self::rc_coordinate(row, col))
+    }
+
+    /// Plays `value` at the 0-8 row and column, see `make_move`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the row or column is out of range
+    pub fn set_rc(&mut self, row: usize, col: usize, value: u8) -> SudokuMoveResult {
+        self.make_move(&SudokuMove {
+            cell_coordinate: Self::rc_coordinate(row, col),
+            value,
+        })
+    }
+
+    fn rc_coordinate(row: usize, col: usize) -> CellCoordinate {
+        CellCoordinate::from_rc(row, col)
+            .unwrap_or_else(|| panic!("row {} and column {} must both be in 0-8", row, col))
+    }
+
+    // The cells of the move's row that already hold its value
+    fn get_row_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
+        let (row, _) = sudoku_move.cell_coordinate.to_rc();
+        Self::move_duplicates(self.row_cells(row), sudoku_move)
+    }
+
+    fn get_column_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
+        let (_, col) = sudoku_move.cell_coordinate.to_rc();
+        Self::move_duplicates(self.column_cells(col), sudoku_move)
+    }
+
+    fn get_box_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
+        Self::move_duplicates(
+            self.box_cells(sudoku_move.cell_coordinate.box_index()),
+            sudoku_move,
+        )
+    }
+
+    // Duplicates on the diagonals through the move's cell, only checked under X-Sudoku rules
+    fn get_diagonal_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
+        let duplicates: Vec<CellCoordinate> = self
+            .diagonals_through(sudoku_move.cell_coordinate)
+            .filter_map(|diagonal| {
+                let diagonal_cells = Self::diagonal_coordinates(diagonal)
+                    .map(|cell_coordinate| (cell_coordinate, self.get_value(cell_coordinate)));
+                Self::move_duplicates(diagonal_cells, sudoku_move)
+            })
+            .flatten()
+            .collect();
+        if !duplicates.is_empty() {
+            Some(duplicates)
+        } else {
+            None
+        }
+    }
+
+    // Same answer as the duplicate checks all finding nothing, without their allocations.
+    // The solver calls this for every value it tries
+    fn fits_units(&self, sudoku_move: &SudokuMove) -> bool {
+        let (row, col) = sudoku_move.cell_coordinate.to_rc();
+        let diagonals = self
+            .diagonals_through(sudoku_move.cell_coordinate)
+            .flat_map(Self::diagonal_coordinates);
+        Self::row_coordinates(row)
+            .chain(Self::column_coordinates(col))
+            .chain(Self::box_coordinates(
+                sudoku_move.cell_coordinate.box_index(),
+            ))
+            .chain(diagonals)
+            .all(|cell_coordinate| {
+                cell_coordinate == sudoku_move.cell_coordinate
+                    || self.get_value(cell_coordinate) != Some(sudoku_move.value)
+            })
+    }
+
+    // Duplicates the unit would have with the move's value in its cell, other than that cell
+    fn move_duplicates(
+        unit: impl Iterator<Item = (CellCoordinate, Option<u8>)>,
+        sudoku_move: &SudokuMove,
+    ) -> Option<Vec<CellCoordinate>> {
+        let cells_with_move = unit.map(|(cell_coordinate, value)| {
+            if cell_coordinate == sudoku_move.cell_coordinate {
+                (cell_coordinate, Some(sudoku_move.value))
+            } else {
+                (cell_coordinate, value)
+            }
+        });
+        let duplicates: Vec<CellCoordinate> = duplicates_in_unit(cells_with_move)
+            .into_iter()
+            .filter(|(cell_coordinate, value)| {
+                *value == sudoku_move.value && *cell_coordinate != sudoku_move.cell_coordinate
+            })
+            .map(|(cell_coordinate, _)| cell_coordinate)
+            .collect();
+        if !duplicates.is_empty() {
+            Some(duplicates)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the move unless its value is already in the move's sub-grid, row or column, or one
+    /// of its diagonals under X-Sudoku rules. An invalid move leaves the board untouched and
+    /// reports the cells it conflicts with along with the move's cell
+    pub fn make_move(&mut self, sudoku_move: &SudokuMove) -> SudokuMoveResult {
+        let move_result = self.play_move(sudoku_move);
+        // Skips the coordinate conversion too unless debug records are enabled
+        if log::log_enabled!(log::Level::Debug) {
+            let (row, col) = sudoku_move.cell_coordinate.to_rc();
+            log::debug!(
+                "move at row {}, column {}: {} -> {:?}",
+                row,
+                col,
+                sudoku_move.value,
+                move_result
+            );
+        }
+        move_result
+    }
+
+    // `make_move` without the logging
+    fn play_move(&mut self, sudoku_move: &SudokuMove) -> SudokuMoveResult {
+        if let Err(cell_error) = self
+            .sub_grid(sudoku_move.cell_coordinate.sub_grid)
+            .check_writable(sudoku_move.cell_coordinate.cell, sudoku_move.value)
+        {
+            return SudokuMoveResult::Rejected(cell_error);
+        }
+
+        let mut invalid_cells_coordinates = self.would_conflict(sudoku_move);
+        if !invalid_cells_coordinates.is_empty() {
+            invalid_cells_coordinates.push(sudoku_move.cell_coordinate);
+        } else if let Err(cell_error) =
+            self.update_value(sudoku_move.cell_coordinate, sudoku_move.value)
+        {
+            return SudokuMoveResult::Rejected(cell_error);
+        }
+
+        if !invalid_cells_coordinates.is_empty() {
+            SudokuMoveResult::Invalid(invalid_cells_coordinates)
+        } else if self.reference_solution.as_ref().is_some_and(|solution| {
+            solution.get_value(sudoku_move.cell_coordinate) != Some(sudoku_move.value)
+        }) {
+            SudokuMoveResult::WrongValue(sudoku_move.cell_coordinate)
+        } else {
+            SudokuMoveResult::Ok {
+                cell_coordinate: sudoku_move.cell_coordinate,
+                value: sudoku_move.value,
+            }
+        }
+    }
+
+    /// Cheap variant of `make_move` that stops at the first conflict instead of collecting them
+    /// all. Returns whether the move was played, which is false exactly when `make_move` would
+    /// answer `Invalid` or `Rejected`. The reference solution isn't consulted
+    pub fn make_move_fast(&mut self, sudoku_move: &SudokuMove) -> bool {
+        let writable = self
+            .sub_grid(sudoku_move.cell_coordinate.sub_grid)
+            .check_writable(sudoku_move.cell_coordinate.cell, sudoku_move.value)
+            .is_ok();
+        writable
+            && self.fits_units(sudoku_move)
+            && self
+                .update_value(sudoku_move.cell_coordinate, sudoku_move.value)
+                .is_ok()
+    }
+
+    /// The cells that already hold the move's value in its sub-grid, row or column (and
+    /// diagonals under X-Sudoku rules), so playing it would conflict with them. The board isn't
+    /// touched
+    pub fn would_conflict(&self, sudoku_move: &SudokuMove) -> Vec<CellCoordinate> {
+        let mut conflicts: Vec<CellCoordinate> = [
+            self.get_box_duplicates(sudoku_move),
+            self.get_row_duplicates(sudoku_move),
+            self.get_column_duplicates(sudoku_move),
+            self.get_diagonal_duplicates(sudoku_move),
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect();
+        let mut reported_cells = HashSet::new();
+        conflicts.retain(|cell_coordinate| reported_cells.insert(*cell_coordinate));
+        conflicts
+    }
+
+    /// Plays the moves in order and returns each result tagged with the index of its move. With
+    /// `stop_on_failure` nothing after the first move that isn't `Ok` is played
+    pub fn apply_moves(
+        &mut self,
+        moves: &[SudokuMove],
+        stop_on_failure: bool,
+    ) -> Vec<(usize, SudokuMoveResult)> {
+        let mut move_results = Vec::with_capacity(moves.len());
+        for (index, sudoku_move) in moves.iter().enumerate() {
+            let move_result = self.make_move(sudoku_move);
+            let failed = !matches!(move_result, SudokuMoveResult::Ok { .. });
+            move_results.push((index, move_result));
+            if failed && stop_on_failure {
+                break;
+            }
+        }
+        move_results
+    }
+
+    /// Digits already placed nine times, which can't go anywhere else on the board
+    pub fn completed_digits(&self) -> Vec<u8> {
+        let digit_counts = self.digit_counts();
+        (1..=9)
+            .filter(|value| digit_counts[*value as usize] >= 9)
+            .collect()
+    }
+
+    /// How many of each digit are left to place, index 0 is digit 1. Digits placed more than nine
+    /// times, which only happens on an invalid board, count as none left
+    pub fn remaining_counts(&self) -> [usize; 9] {
+        let digit_counts = self.digit_counts();
+        let mut remaining_counts = [0; 9];
+        for (index, remaining_count) in remaining_counts.iter_mut().enumerate() {
+            *remaining_count = 9_usize.saturating_sub(digit_counts[index + 1]);
+        }
+        remaining_counts
+    }
+
+    /// Filled and empty cell counts, how often each digit is placed and whether the board is valid
+    /// and complete, gathered in one pass
+    pub fn stats(&self) -> BoardStats {
+        let counts_by_value = self.digit_counts();
+        let mut digit_counts = [0; 9];
+        digit_counts.copy_from_slice(&counts_by_value[1..]);
+        let filled_cells = digit_counts.iter().sum();
+        let is_valid = self.is_valid();
+        BoardStats {
+            filled_cells,
+            empty_cells: 81 - filled_cells,
+            digit_counts,
+            is_valid,
+            is_complete: is_valid && filled_cells == 81,
+        }
+    }
+
+    // How often each digit appears on the board, indexed by the digit itself
+    fn digit_counts(&self) -> [usize; 10] {
+        let mut digit_counts = [0; 10];
+        for (_, value) in self.iter_cells() {
+            if let Some(value) = value {
+                digit_counts[value as usize] += 1;
+            }
+        }
+        digit_counts
+    }
+
+    /// Digits that don't appear yet in the cell's row, column or sub-grid, or its diagonals under
+    /// X-Sudoku rules. A filled cell has no candidates, so the result is empty for it
+    pub fn legal_candidates(&self, cell_coordinate: &CellCoordinate) -> HashSet<u8> {
+        if self.get_value(*cell_coordinate).is_some() {
+            return HashSet::new();
+        }
+        (1..=9)
+            .filter(|value| {
+                let sudoku_move = SudokuMove {
+                    cell_coordinate: *cell_coordinate,
+                    value: *value,
+                };
+                self.fits_units(&sudoku_move)
+            })
+            .collect()
+    }
+
+    /// The cells sharing a row, column or sub-grid with the cell, itself excluded: 20 on a classic
+    /// board. Under X-Sudoku rules the cells of its diagonals are peers too
+    pub fn peers(&self, cell_coordinate: &CellCoordinate) -> HashSet<CellCoordinate> {
+        let (row, col) = cell_coordinate.to_rc();
+        let diagonals = self
+            .diagonals_through(*cell_coordinate)
+            .flat_map(Self::diagonal_coordinates);
+        Self::row_coordinates(row)
+            .chain(Self::column_coordinates(col))
+            .chain(Self::box_coordinates(cell_coordinate.box_index()))
+            .chain(diagonals)
+            .filter(|peer| peer != cell_coordinate)
+            .collect()
+    }
+
+    /// Cells holding the value, in reading order
+    pub fn cells_with_value(&self, value: u8) -> Vec<CellCoordinate> {
+        self.iter_cells()
+            .filter(|(_, cell_value)| *cell_value == Some(value))
+            .map(|(cell_coordinate, _)| cell_coordinate)
+            .collect()
+    }
+
+    /// Empty cells, in reading order, where the value could go without repeating in a row,
+    /// column or sub-grid, or a diagonal under X-Sudoku rules. Nothing for values outside 1-9
+    pub fn placements_for(&self, value: u8) -> Vec<CellCoordinate> {
+        self.iter_cells()
+            .map(|(cell_coordinate, _)| cell_coordinate)
+            .filter(|cell_coordinate| self.legal_candidates(cell_coordinate).contains(&value))
+            .collect()
+    }
+
+    /// True when every filled cell of this board holds the same value in `other`, empty cells are
+    /// ignored. A partial board is consistent with its solution
+    pub fn is_consistent_with(&self, other: &SudokuBoard) -> bool {
+        self.iter_cells().all(|(cell_coordinate, value)| {
+            value.is_none() || value == other.get_value(cell_coordinate)
+        })
+    }
+
+    /// True when no filled cell repeats its digit within its row, column or sub-grid, or its
+    /// diagonals under X-Sudoku rules
+    pub fn is_valid(&self) -> bool {
+        self.all_conflicts().is_empty()
+    }
+
+    /// True only when every cell is filled and the board is valid
+    pub fn is_complete(&self) -> bool {
+        self.empty_cells().is_empty() && self.is_valid()
+    }
+
+    /// Every filled cell that shares its digit with another cell of its row, column or sub-grid,
+    /// or of its diagonals under X-Sudoku rules
+    pub fn all_conflicts(&self) -> Vec<CellCoordinate> {
+        let mut conflicts = Vec::new();
+        for cell_coordinate in Self::cells_in_order() {
+            let Some(value) = self.get_value(cell_coordinate) else {
+                continue;
+            };
+            let sudoku_move = SudokuMove {
+                cell_coordinate,
+                value,
+            };
+            if self.get_box_duplicates(&sudoku_move).is_some()
+                || self.get_row_duplicates(&sudoku_move).is_some()
+                || self.get_column_duplicates(&sudoku_move).is_some()
+                || self.get_diagonal_duplicates(&sudoku_move).is_some()
+            {
+                conflicts.push(cell_coordinate);
+            }
+        }
+        conflicts
+    }
+
+    /// The cells of `all_conflicts` as a set, for membership checks at any time
+    pub fn conflicting_cells(&self) -> HashSet<CellCoordinate> {
+        self.all_conflicts().into_iter().collect()
+    }
+
+    /// Conflicting cells grouped by the row, column or sub-grid they clash in, only units holding
+    /// a conflict are listed. A cell clashing in several units is listed under each of them
+    pub fn conflicts_by_unit(&self) -> Vec<(UnitId, Vec<CellCoordinate>)> {
+        let mut conflicts = Vec::new();
+        for (unit_id, unit) in self.units() {
+            let mut digit_counts = [0; 10];
+            for cell_coordinate in &unit {
+                if let Some(value) = self.get_value(*cell_coordinate) {
+                    digit_counts[value as usize] += 1;
+                }
+            }
+            let conflicting_cells: Vec<CellCoordinate> = unit
+                .into_iter()
+                .filter(|cell_coordinate| {
+                    self.get_value(*cell_coordinate)
+                        .is_some_and(|value| digit_counts[value as usize] > 1)
+                })
+                .collect();
+            if !conflicting_cells.is_empty() {
+                conflicts.push((unit_id, conflicting_cells));
+            }
+        }
+        conflicts
+    }
+
+    /// Rows, columns and sub-grids (and diagonals under X-Sudoku rules) holding each of 1-9 once
+    pub fn completed_units(&self) -> Vec<UnitId> {
+        self.units()
+            .into_iter()
+            .filter(|(_, unit)| {
+                let values: HashSet<u8> = unit
+                    .iter()
+                    .filter_map(|cell_coordinate| self.get_value(*cell_coordinate))
+                    .collect();
+                values.len() == 9
+            })
+            .map(|(unit_id, _)| unit_id)
+            .collect()
+    }
+
+    /// Repeatedly fills every empty cell that has a single legal candidate, until no such cell is
+    /// left. Returns how many cells were filled
+    pub fn fill_naked_singles(&mut self) -> usize {
+        let mut nr_filled = 0;
+        loop {
+            let mut nr_filled_in_pass = 0;
+            for cell_coordinate in self.empty_cells_in_order() {
+                let candidates = self.legal_candidates(&cell_coordinate);
+                if candidates.len() != 1 {
+                    continue;
+                }
+                if let Some(value) = candidates.into_iter().next() {
+                    // The cell is empty and so not a given, and candidates are 1-9
+                    let _ = self.update_value(cell_coordinate, value);
+                    nr_filled_in_pass += 1;
+                }
+            }
+            if nr_filled_in_pass == 0 {
+                return nr_filled;
+            }
+            nr_filled += nr_filled_in_pass;
+        }
+    }
+
+    /// Repeatedly places every digit that fits in only one cell of a row, column or sub-grid,
+    /// until no such digit is left. Returns how many cells were filled
+    pub fn fill_hidden_singles(&mut self) -> usize {
+        let mut nr_filled = 0;
+        loop {
+            let mut nr_filled_in_pass = 0;
+            for (_, unit) in self.units() {
+                nr_filled_in_pass += self.fill_hidden_singles_in_unit(&unit);
+            }
+            if nr_filled_in_pass == 0 {
+                return nr_filled;
+            }
+            nr_filled += nr_filled_in_pass;
+        }
+    }
+
+    // Shared by rows, columns and sub-grids, `unit` holds the nine cells of one of them
+    fn fill_hidden_singles_in_unit(&mut self, unit: &[CellCoordinate]) -> usize {
+        let mut nr_filled = 0;
+        for value in 1..=9 {
+            if let Some(cell_coordinate) = self.hidden_single_in_unit(unit, value) {
+                let _ = self.update_value(cell_coordinate, value);
+                nr_filled += 1;
+            }
+        }
+        nr_filled
+    }
+
+    // The only cell of the unit the value fits in, None when it fits in several or none
+    fn hidden_single_in_unit(&self, unit: &[CellCoordinate], value: u8) -> Option<CellCoordinate> {
+        let places: Vec<CellCoordinate> = unit
+            .iter()
+            .filter(|cell_coordinate| self.legal_candidates(cell_coordinate).contains(&value))
+            .copied()
+            .collect();
+        match places[..] {
+            [cell_coordinate] => Some(cell_coordinate),
+            _ => None,
+        }
+    }
+
+    /// The easiest deduction available, for guided hints: the first naked single in reading
+    /// order, otherwise the first hidden single found going through the rows, columns and
+    /// sub-grids. None when the board needs harder techniques or is complete. The board isn't
+    /// touched
+    pub fn next_logical_step(&self) -> Option<LogicalStep> {
+        let empty_cells = self.empty_cells();
+        let naked_single = empty_cells.iter().find_map(|cell_coordinate| {
+            let candidates = self.legal_candidates(cell_coordinate);
+            match candidates.len() {
+                1 => candidates.into_iter().next().map(|value| LogicalStep {
+                    cell_coordinate: *cell_coordinate,
+                    value,
+                    technique: Technique::NakedSingle,
+                }),
+                _ => None,
+            }
+        });
+        naked_single.or_else(|| {
+            self.units().into_iter().find_map(|(_, unit)| {
+                (1..=9).find_map(|value| {
+                    self.hidden_single_in_unit(&unit, value)
+                        .map(|cell_coordinate| LogicalStep {
+                            cell_coordinate,
+                            value,
+                            technique: Technique::HiddenSingle,
+                        })
+                })
+            })
+        })
+    }
+
+    /// Classifies the puzzle by the hardest technique needed to solve it: naked singles alone
+    /// rate Easy, also needing hidden singles rates Medium and needing guesses rates Hard.
+    /// Expert is never returned, the techniques can't tell it apart from Hard
+    pub fn rate(&self) -> Difficulty {
+        let mut board = self.clone();
+        board.fill_naked_singles();
+        if board.is_complete() {
+            return Difficulty::Easy;
+        }
+        while board.fill_hidden_singles() + board.fill_naked_singles() > 0 {}
+        if board.is_complete() {
+            Difficulty::Medium
+        } else {
+            Difficulty::Hard
+        }
+    }
+
+    /// Fills every empty cell using recursive backtracking. Returns false and leaves the board
+    /// untouched when no complete valid solution exists
+    pub fn solve(&mut self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        let empty_cells = self.empty_cells_in_order();
+        self.solve_cells(&empty_cells)
+    }
+
+    /// Same as `solve`, but always branches on the empty cell with the fewest legal candidates
+    /// first, which prunes far more of the search on hard puzzles
+    pub fn solve_with_heuristic(&mut self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        self.solve_most_constrained()
+    }
+
+    // Picks the empty cell with the fewest candidates each step, a cell with none fails at once
+    fn solve_most_constrained(&mut self) -> bool {
+        let Some((cell_coordinate, candidates)) = self
+            .empty_cells()
+            .into_iter()
+            .map(|cell_coordinate| (cell_coordinate, self.legal_candidates(&cell_coordinate)))
+            .min_by_key(|(_, candidates)| candidates.len())
+        else {
+            return true;
+        };
+        let mut candidates: Vec<u8> = candidates.into_iter().collect();
+        candidates.sort_unstable();
+        for value in candidates {
+            // Legal candidates of an empty cell always fit, and the cell is never a given
+            let _ = self.update_value(cell_coordinate, value);
+            if self.solve_most_constrained() {
+                return true;
+            }
+            let _ = self.clear_value(cell_coordinate);
+        }
+        false
+    }
+
+    /// Counts the complete valid fillings of the board without mutating it. Counting stops as
+    /// soon as `limit` solutions were found, so `count_solutions(2)` cheaply checks uniqueness
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        if limit == 0 || !self.is_valid() {
+            return 0;
+        }
+        let mut board = self.clone();
+        let empty_cells = board.empty_cells_in_order();
+        let mut nr_solutions = 0;
+        board.count_cells(&empty_cells, limit, &mut nr_solutions);
+        nr_solutions
+    }
+
+    fn count_cells(
+        &mut self,
+        empty_cells: &[CellCoordinate],
+        limit: usize,
+        nr_solutions: &mut usize,
+    ) {
+        let Some((cell_coordinate, remaining_cells)) = empty_cells.split_first() else {
+            *nr_solutions += 1;
+            return;
+        };
+        for value in 1..=9 {
+            let sudoku_move = SudokuMove {
+                cell_coordinate: *cell_coordinate,
+                value,
+            };
+            if self.try_place(&sudoku_move) {
+                self.count_cells(remaining_cells, limit, nr_solutions);
+            }
+            let _ = self.clear_value(*cell_coordinate);
+            if *nr_solutions >= limit {
+                return;
+            }
+        }
+    }
+
+    /// Checks the board is a proper puzzle before it's played: no digit repeats in a unit and
+    /// exactly one solution exists
+    pub fn validate_puzzle(&self) -> Result<(), PuzzleError> {
+        let conflicts = self.all_conflicts();
+        if !conflicts.is_empty() {
+            return Err(PuzzleError::InitialConflict(conflicts));
+        }
+        match self.count_solutions(2) {
+            0 => Err(PuzzleError::Unsolvable),
+            1 => Ok(()),
+            _ => Err(PuzzleError::NotUnique),
+        }
+    }
+
+    /// The solved board when the puzzle has exactly one solution, None when it has none or several.
+    /// Unlike `solve` the board itself is left untouched
+    pub fn solution(&self) -> Option<SudokuBoard> {
+        if self.count_solutions(2) != 1 {
+            return None;
+        }
+        let mut solved_board = self.clone();
+        solved_board.solve().then_some(solved_board)
+    }
+
+    /// Generates a puzzle with a unique solution, leaving fewer clues for harder difficulties.
+    ///
+    /// With `symmetric`, clues are removed together with their partner rotated 180 degrees around
+    /// the center, so the puzzle can end up one clue below the difficulty's count
+    pub fn generate<R: Rng + ?Sized>(
+        difficulty: Difficulty,
+        symmetric: bool,
+        rng: &mut R,
+    ) -> SudokuBoard {
+        let mut board = SudokuBoard::new();
+        let all_cells = Self::cells_in_order();
+        board.fill_cells_randomly(&all_cells, rng);
+
+        let mut removal_order = all_cells;
+        removal_order.shuffle(rng);
+        let mut nr_givens = removal_order.len();
+        for cell_coordinate in removal_order {
+            if nr_givens <= difficulty.givens() {
+                break;
+            }
+            let mut removed_cells = vec![cell_coordinate];
+            if symmetric {
+                let (row, col) = cell_coordinate.to_rc();
+                if let Some(partner) = CellCoordinate::from_rc(8 - row, 8 - col) {
+                    if partner != cell_coordinate {
+                        removed_cells.push(partner);
+                    }
+                }
+            }
+            // Symmetric removal keeps partners both filled or both empty
+            let removed_values: Vec<(CellCoordinate, u8)> = removed_cells
+                .iter()
+                .filter_map(|removed_cell| {
+                    board
+                        .get_value(*removed_cell)
+                        .map(|value| (*removed_cell, value))
+                })
+                .collect();
+            if removed_values.len() != removed_cells.len() {
+                continue;
+            }
+            for (removed_cell, _) in &removed_values {
+                let _ = board.clear_value(*removed_cell);
+            }
+            if board.count_solutions(2) == 1 {
+                nr_givens -= removed_values.len();
+            } else {
+                // Restores values that were just read from these cells
+                for (removed_cell, value) in removed_values {
+                    let _ = board.update_value(removed_cell, value);
+                }
+            }
+        }
+
+        for cell_coordinate in Self::cells_in_order() {
+            if let Some(value) = board.get_value(cell_coordinate) {
+                let _ = board.set_given(cell_coordinate, value);
+            }
+        }
+        board
+    }
+
+    /// Reproducible variant of `generate`: the same seed and difficulty always give the same puzzle
+    pub fn generate_with_seed(difficulty: Difficulty, seed: u64) -> SudokuBoard {
+        // ChaCha's output is stable across rand releases, unlike `StdRng`
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        Self::generate(difficulty, false, &mut rng)
+    }
+
+    // Same search as `solve_cells`, trying the candidate values in random order
+    fn fill_cells_randomly<R: Rng + ?Sized>(
+        &mut self,
+        empty_cells: &[CellCoordinate],
+        rng: &mut R,
+    ) -> bool {
+        let Some((cell_coordinate, remaining_cells)) = empty_cells.split_first() else {
+            return true;
+        };
+        let mut values: Vec<u8> = (1..=9).collect();
+        values.shuffle(rng);
+        for value in values {
+            let sudoku_move = SudokuMove {
+                cell_coordinate: *cell_coordinate,
+                value,
+            };
+            if self.try_place(&sudoku_move) && self.fill_cells_randomly(remaining_cells, rng) {
+                return true;
+            }
+            let _ = self.clear_value(*cell_coordinate);
+        }
+        false
+    }
+
+    // The search only ever clears cells it filled itself, never givens, so clearing can't fail
+    fn solve_cells(&mut self, empty_cells: &[CellCoordinate]) -> bool {
+        let Some((cell_coordinate, remaining_cells)) = empty_cells.split_first() else {
+            return true;
+        };
+        for value in 1..=9 {
+            let sudoku_move = SudokuMove {
+                cell_coordinate: *cell_coordinate,
+                value,
+            };
+            if self.try_place(&sudoku_move) && self.solve_cells(remaining_cells) {
+                return true;
+            }
+            let _ = self.clear_value(*cell_coordinate);
+        }
+        false
+    }
+
+    // Places the value on an empty cell only if it clashes with nothing in its row, column and
+    // sub-grid, or its diagonals under X-Sudoku rules
+    fn try_place(&mut self, sudoku_move: &SudokuMove) -> bool {
+        if !self.fits_units(sudoku_move) {
+            return false;
+        }
+        self.update_value(sudoku_move.cell_coordinate, sudoku_move.value)
+            .is_ok()
+    }
+
+    /// Unfilled cells in reading order, row 0 first and column 0 first within it
+    pub fn empty_cells(&self) -> Vec<CellCoordinate> {
+        self.iter_cells()
+            .filter(|(_, value)| value.is_none())
+            .map(|(cell_coordinate, _)| cell_coordinate)
+            .collect()
+    }
+
+    /// The first empty cell after the given one in reading order. The search wraps from the last
+    /// cell back to the first, so it only finds nothing when no other cell is empty
+    pub fn next_empty_after(&self, cell_coordinate: &CellCoordinate) -> Option<CellCoordinate> {
+        let start = cell_coordinate.to_grid_index();
+        (1..81)
+            .filter_map(|offset| CellCoordinate::from_grid_index((start + offset) % 81))
+            .find(|next_cell| self.get_value(*next_cell).is_none())
+    }
+
+    /// How many cells are still empty
+    pub fn remaining_count(&self) -> usize {
+        self.iter_cells()
+            .filter(|(_, value)| value.is_none())
+            .count()
+    }
+
+    /// True when exactly one cell is left to fill
+    pub fn is_almost_complete(&self) -> bool {
+        self.remaining_count() == 1
+    }
+
+    /// Unfilled cells sub-grid by sub-grid, the order the solver fills them in
+    pub fn empty_cells_in_order(&self) -> Vec<CellCoordinate> {
+        Self::cells_in_order()
+            .into_iter()
+            .filter(|cell_coordinate| self.get_value(*cell_coordinate).is_none())
+            .collect()
+    }
+
+    // The units a digit may appear only once in: the nine rows, columns and sub-grids, plus the
+    // two diagonals under X-Sudoku rules
+    fn units(&self) -> Vec<(UnitId, Vec<CellCoordinate>)> {
+        let rows = (0..9).map(|row| (UnitId::Row(row), Self::row_coordinates(row).collect()));
+        let columns =
+            (0..9).map(|col| (UnitId::Column(col), Self::column_coordinates(col).collect()));
+        let boxes = (0..9).map(|box_index| {
+            (
+                UnitId::Box(box_index),
+                Self::box_coordinates(box_index).collect(),
+            )
+        });
+        let diagonals = match self.variant {
+            Variant::Classic => 0..0,
+            Variant::XSudoku => 0..2,
+        }
+        .map(|diagonal| {
+            (
+                UnitId::Diagonal(diagonal),
+                Self::diagonal_coordinates(diagonal).collect(),
+            )
+        });
+        rows.chain(columns).chain(boxes).chain(diagonals).collect()
+    }
+
+    /// The board turned a quarter turn clockwise, the top row becomes the right column
+    pub fn rotate_90(&self) -> SudokuBoard {
+        self.transformed(|row, col| (col, 8 - row), &|value| value)
+    }
+
+    /// The board mirrored top to bottom, the top row becomes the bottom row
+    pub fn reflect_horizontal(&self) -> SudokuBoard {
+        self.transformed(|row, col| (8 - row, col), &|value| value)
+    }
+
+    /// The board mirrored left to right, the left column becomes the right column
+    pub fn reflect_vertical(&self) -> SudokuBoard {
+        self.transformed(|row, col| (row, 8 - col), &|value| value)
+    }
+
+    /// The board with every digit `d` replaced by `mapping[d - 1]`, an equivalent puzzle when
+    /// `mapping` holds each of 1-9 once.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `mapping` isn't a permutation of 1-9
+    pub fn relabel(&self, mapping: [u8; 9]) -> SudokuBoard {
+        let mut sorted_mapping = mapping;
+        sorted_mapping.sort_unstable();
+        assert!(
+            sorted_mapping == [1, 2, 3, 4, 5, 6, 7, 8, 9],
+            "mapping {:?} must hold each of 1-9 once",
+            mapping
+        );
+        self.transformed(|row, col| (row, col), &|value| mapping[value as usize - 1])
+    }
+
+    // Copies every cell to the 0-8 row and column `target` maps it to, with its digits passed
+    // through `relabel`. Givens, pencil marks and the reference solution are carried along, both
+    // maps must be bijections
+    fn transformed(
+        &self,
+        target: fn(usize, usize) -> (usize, usize),
+        relabel: &impl Fn(u8) -> u8,
+    ) -> SudokuBoard {
+        let mut board = SudokuBoard::with_variant(self.variant);
+        for (cell_coordinate, value) in self.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            let (target_row, target_col) = target(row, col);
+            let target_coordinate = Self::rc_coordinate(target_row, target_col);
+            // Values come from a board, so they're in range and every target is written once
+            if let Some(value) = value {
+                if self.is_given(cell_coordinate) {
+                    let _ = board.set_given(target_coordinate, relabel(value));
+                } else {
+                    let _ = board.update_value(target_coordinate, relabel(value));
+                }
+            }
+            for candidate in self.get_candidates(cell_coordinate) {
+                let _ = board.toggle_candidate(target_coordinate, relabel(candidate));
+            }
+        }
+        board.reference_solution = self
+            .reference_solution
+            .as_ref()
+            .map(|solution| Box::new(solution.transformed(target, relabel)));
+        board
+    }
+
+    // Diagonal 0 runs from the top left corner, 1 from the top right one
+    fn diagonal_coordinates(diagonal: usize) -> impl Iterator<Item = CellCoordinate> {
+        (0..9).filter_map(move |row| match diagonal {
+            0 => CellCoordinate::from_rc(row, row),
+            1 => CellCoordinate::from_rc(row, 8 - row),
+            _ => None,
+        })
+    }
+
+    // The diagonals the cell lies on that the variant checks, the center is on both
+    fn diagonals_through(&self, cell_coordinate: CellCoordinate) -> impl Iterator<Item = usize> {
+        let (row, col) = cell_coordinate.to_rc();
+        let checks_diagonals = self.variant == Variant::XSudoku;
+        [(0, row == col), (1, row + col == 8)]
+            .into_iter()
+            .filter(move |(_, on_diagonal)| checks_diagonals && *on_diagonal)
+            .map(|(diagonal, _)| diagonal)
+    }
+
+    fn row_coordinates(row: usize) -> impl Iterator<Item = CellCoordinate> {
+        (0..9).filter_map(move |col| CellCoordinate::from_rc(row, col))
+    }
+
+    fn column_coordinates(col: usize) -> impl Iterator<Item = CellCoordinate> {
+        (0..9).filter_map(move |row| CellCoordinate::from_rc(row, col))
+    }
+
+    fn box_coordinates(box_index: usize) -> impl Iterator<Item = CellCoordinate> {
+        let (first_row, first_col) = (box_index / 3 * 3, box_index % 3 * 3);
+        (0..9).filter_map(move |offset| {
+            CellCoordinate::from_rc(first_row + offset / 3, first_col + offset % 3)
+        })
+    }
+
+    // All cells ordered by sub-grid row-major, then cell row-major inside each sub-grid
+    fn cells_in_order() -> Vec<CellCoordinate> {
+        let mut cells = Vec::with_capacity(81);
+        for sub_grid_row in Row::all() {
+            for sub_grid_col in Column::all() {
+                for cell_row in Row::all() {
+                    for cell_col in Column::all() {
+                        cells.push(CellCoordinate {
+                            sub_grid: PositionId {
+                                row: *sub_grid_row,
+                                column: *sub_grid_col,
+                            },
+                            cell: PositionId {
+                                row: *cell_row,
+                                column: *cell_col,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// One line per row labelled R1-R9, columns split into blocks of three, e.g.
+/// `R1: 5 3 . | . 7 . | . . .`, so failing assertions show the grid
+impl fmt::Debug for SudokuBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (cell_coordinate, value) in self.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            if col == 0 {
+                if row > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "R{}:", row + 1)?;
+            } else if col % 3 == 0 {
+                write!(f, " |")?;
+            }
+            match value {
+                Some(value) => write!(f, " {}", value)?,
+                None => write!(f, " .")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the board as a 9x9 ASCII grid, `.` marking empty cells and `+---+` lines around
+/// the 3x3 blocks
+impl fmt::Display for SudokuBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BLOCK_SEPARATOR: &str = "+---+---+---+";
+        for (cell_coordinate, value) in self.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            if col == 0 && row % 3 == 0 {
+                writeln!(f, "{}", BLOCK_SEPARATOR)?;
+            }
+            if col % 3 == 0 {
+                write!(f, "|")?;
+            }
+            match value {
+                Some(value) => write!(f, "{}", value)?,
+                None => write!(f, ".")?,
+            }
+            if col == 8 {
+                writeln!(f, "|")?;
+            }
+        }
+        write!(f, "{}", BLOCK_SEPARATOR)
+    }
+}
+
+// serde only implements arrays of up to 32 elements, so the cells go through a sequence
+impl Serialize for FlatBoard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for FlatBoard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cells = Vec::<Option<u8>>::deserialize(deserializer)?;
+        let nr_cells = cells.len();
+        cells
+            .try_into()
+            .map(FlatBoard)
+            .map_err(|_| de::Error::invalid_length(nr_cells, &"81 cells"))
+    }
+}
+
+impl From<&SudokuBoard> for FlatBoard {
+    fn from(board: &SudokuBoard) -> Self {
+        let mut cells = [None; 81];
+        for (cell_coordinate, value) in board.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            cells[row * 9 + col] = value;
+        }
+        FlatBoard(cells)
+    }
+}
+
+/// Fails with `ValueOutOfRange` when a cell holds something other than 1-9
+impl TryFrom<FlatBoard> for SudokuBoard {
+    type Error = CellError;
+
+    fn try_from(flat_board: FlatBoard) -> Result<Self, Self::Error> {
+        let mut board = SudokuBoard::new();
+        for (index, value) in flat_board.0.into_iter().enumerate() {
+            if let (Some(value), Some(cell_coordinate)) =
+                (value, CellCoordinate::from_rc(index / 9, index % 9))
+            {
+                board.update_value(cell_coordinate, value)?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*; // Import functions from the parent module
+    use crate::test_fixtures::*;
+
+    #[test]
+    fn new_subgrid_returns_all_empty_cells() {
+        let empty_subgrid = SubGrid::new();
+        for cell in empty_subgrid.cells.iter().flatten() {
+            assert_eq!(None, *cell);
+        }
+    }
+
+    #[test]
+    fn row_and_column_from_index() {
+        for (index, row, column) in [
+            (1, Row::Upper, Column::Left),
+            (2, Row::Center, Column::Center),
+            (3, Row::Bottom, Column::Right),
+        ] {
+            assert_eq!(Some(row), Row::from_index(index));
+            assert_eq!(Some(column), Column::from_index(index));
+            assert_eq!(index, row.to_index());
+            assert_eq!(index, column.to_index());
+        }
+        for out_of_range_index in [0, 4] {
+            assert_eq!(None, Row::from_index(out_of_range_index));
+            assert_eq!(None, Column::from_index(out_of_range_index));
+        }
+    }
+
+    #[test]
+    fn update_cell_value_in_subgrid() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {  
+            row: Row::Center,
+            column: Column::Right
+        };
+        let arbitrary_value = 8_u8;
+        let _ = mut_subgrid.update_value(arbitrary_position, arbitrary_value);
+        assert_eq!(arbitrary_value, mut_subgrid.get_value(arbitrary_position).expect("Value just updated, shouldn't be None") );
+    }
+
+    #[test]
+    fn update_cell_in_subgrid_with_invalid_value_fails() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {  
+            row: Row::Center,
+            column: Column::Right
+        };
+        for arbitrary_invalid_value in [0_u8, 10_u8] {
+            let ret_err = mut_subgrid.update_value(arbitrary_position, arbitrary_invalid_value);
+            assert_eq!(Err(CellError::ValueOutOfRange(arbitrary_invalid_value)), ret_err);
+            assert_eq!(None, mut_subgrid.get_value(arbitrary_position));
+        }
+    }
+
+    #[test]
+    fn get_value_from_subgrid() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {  
+            row: Row::Center,
+            column: Column::Left
+        };
+        let arbitrary_empty_cell = PositionId {  
+            row: Row::Bottom,
+            column: Column::Center
+        };
+        let arbitrary_value = 6_u8;
+        let _ = mut_subgrid.update_value(arbitrary_position, arbitrary_value);
+        assert_eq!(arbitrary_value, mut_subgrid.get_value(arbitrary_position).expect("Value just updated, shouldn't be None"));
+        assert_eq!(None, mut_subgrid.get_value(arbitrary_empty_cell));
+    }
+
+    #[test]
+    fn make_no_duplicate_move_in_subgrid() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {  
+            row: Row::Center,
+            column: Column::Left
+        };
+        let arbitrary_value = 6_u8;
+        let sub_grid_move = SubGridMove { 
+            cell: arbitrary_position,
+            value: arbitrary_value
+        };
+
+        let move_result = mut_subgrid.make_move(sub_grid_move);
+        assert_eq!(SubgridMoveResult::Ok, move_result);
+        assert_eq!(arbitrary_value, mut_subgrid.get_value(arbitrary_position).expect("Value just updated, shouldn't be None"));
+    }
+
+    #[test]
+    fn make_duplicate_move_in_subgrid() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {  
+            row: Row::Center,
+            column: Column::Left
+        };
+        let arbitrary_value = 6_u8;
+        let sub_grid_move_1 = SubGridMove { 
+            cell: arbitrary_position,
+            value: arbitrary_value
+        };
+
+        let arbitrary_position_2 = PositionId {  
+            row: Row::Center,
+            column: Column::Right
+        };
+        let sub_grid_move_2 = SubGridMove { 
+            cell: arbitrary_position_2,
+            value: arbitrary_value
+        };
+
+        let _ = mut_subgrid.make_move(sub_grid_move_1);
+        let invalid_move = mut_subgrid.make_move(sub_grid_move_2);
+        
+        // Use pattern matching to extract the vector and compare
+        if let SubgridMoveResult::Invalid(positions) = invalid_move {
+            let expected_positions: HashSet<_> = vec![arbitrary_position,arbitrary_position_2 ].into_iter().collect();
+            let actual_positions: HashSet<_> = positions.into_iter().collect();
+            assert_eq!(expected_positions, actual_positions);
+        } else {
+            panic!("Expected SubgridMoveResult::Invalid, got {:?}", invalid_move);
+        }
+
+    }
+
+    #[test]
+    fn make_move_with_invalid_value_in_subgrid_is_rejected() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {
+            row: Row::Bottom,
+            column: Column::Left,
+        };
+        let sub_grid_move = SubGridMove {
+            cell: arbitrary_position,
+            value: 10,
+        };
+
+        let move_result = mut_subgrid.make_move(sub_grid_move);
+        assert_eq!(
+            SubgridMoveResult::Rejected(CellError::ValueOutOfRange(10)),
+            move_result
+        );
+        assert_eq!(None, mut_subgrid.get_value(arbitrary_position));
+    }
+
+    #[test]
+    fn make_move_with_invalid_value_on_board_is_rejected() {
+        let mut board = SudokuBoard::new();
+        let sudoku_move = SudokuMove {
+            cell_coordinate: coordinate(4, 4),
+            value: 10,
+        };
+
+        let move_result = board.make_move(&sudoku_move);
+        assert!(matches!(
+            move_result,
+            SudokuMoveResult::Rejected(CellError::ValueOutOfRange(10))
+        ));
+        assert_eq!(None, board.get_value(coordinate(4, 4)));
+    }
+
+    #[test]
+    fn clearing_duplicate_resolves_it_in_subgrid() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {
+            row: Row::Upper,
+            column: Column::Left,
+        };
+        let arbitrary_position_2 = PositionId {
+            row: Row::Bottom,
+            column: Column::Right,
+        };
+        let arbitrary_value = 4_u8;
+        let _ = mut_subgrid.update_value(arbitrary_position, arbitrary_value);
+        let _ = mut_subgrid.update_value(arbitrary_position_2, arbitrary_value);
+        assert!(mut_subgrid.get_duplicates().is_some());
+
+        let _ = mut_subgrid.clear_value(arbitrary_position_2);
+        assert_eq!(None, mut_subgrid.get_value(arbitrary_position_2));
+        assert_eq!(None, mut_subgrid.get_duplicates());
+    }
+
+    #[test]
+    fn solve_already_solved_board() {
+        let mut solved_board = board_from_rows(SOLVED_GRID);
+        assert!(solved_board.solve());
+        assert_eq!(SOLVED_GRID, solved_board.to_array());
+    }
+
+    #[test]
+    fn solve_board_with_unique_solution() {
+        let mut puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert!(puzzle_board.solve());
+        assert_eq!(SOLVED_GRID, puzzle_board.to_array());
+    }
+
+    #[test]
+    fn solve_unsolvable_board_leaves_it_unchanged() {
+        // The top right cell can only hold a 9, which its column already contains
+        let mut unsolvable_grid = [[0_u8; 9]; 9];
+        unsolvable_grid[0] = [1, 2, 3, 4, 5, 6, 7, 8, 0];
+        unsolvable_grid[4][8] = 9;
+        let mut unsolvable_board = board_from_rows(unsolvable_grid);
+
+        assert!(!unsolvable_board.solve());
+        assert_eq!(unsolvable_grid, unsolvable_board.to_array());
+    }
+
+    #[test]
+    fn count_solutions_of_unique_puzzle() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert_eq!(1, puzzle_board.count_solutions(2));
+        assert_eq!(PUZZLE_GRID, puzzle_board.to_array());
+    }
+
+    #[test]
+    fn count_solutions_stops_at_limit() {
+        let empty_board = SudokuBoard::new();
+        assert_eq!(2, empty_board.count_solutions(2));
+        assert_eq!([[0_u8; 9]; 9], empty_board.to_array());
+    }
+
+    #[test]
+    fn generate_puzzle_with_unique_solution() {
+        let puzzle_board = SudokuBoard::generate_with_seed(Difficulty::Easy, 7);
+        let nr_givens = puzzle_board
+            .to_array()
+            .iter()
+            .flatten()
+            .filter(|value| **value != 0)
+            .count();
+        // Clues whose removal breaks uniqueness stay, so there can be more than the target
+        assert!(nr_givens >= EASY_GIVENS);
+        assert_eq!(1, puzzle_board.count_solutions(2));
+    }
+
+    #[test]
+    fn generate_with_same_seed_is_reproducible() {
+        let first_board = SudokuBoard::generate_with_seed(Difficulty::Medium, 42);
+        let second_board = SudokuBoard::generate_with_seed(Difficulty::Medium, 42);
+        let other_seed_board = SudokuBoard::generate_with_seed(Difficulty::Medium, 43);
+        assert_eq!(first_board.to_array(), second_board.to_array());
+        assert_ne!(first_board.to_array(), other_seed_board.to_array());
+    }
+
+    #[test]
+    fn clean_board_is_valid() {
+        let solved_board = board_from_rows(SOLVED_GRID);
+        assert!(solved_board.is_valid());
+        assert!(solved_board.all_conflicts().is_empty());
+        assert!(board_from_rows(PUZZLE_GRID).is_valid());
+    }
+
+    #[test]
+    fn row_conflict_makes_board_invalid() {
+        let mut conflicting_grid = [[0_u8; 9]; 9];
+        conflicting_grid[0][0] = 5;
+        conflicting_grid[0][8] = 5;
+        let conflicting_board = board_from_rows(conflicting_grid);
+        assert!(!conflicting_board.is_valid());
+        assert_eq!(
+            vec![coordinate(0, 0), coordinate(0, 8)],
+            conflicting_board.all_conflicts()
+        );
+    }
+
+    #[test]
+    fn subgrid_conflict_makes_board_invalid() {
+        let mut conflicting_grid = [[0_u8; 9]; 9];
+        conflicting_grid[3][3] = 3;
+        conflicting_grid[5][5] = 3;
+        conflicting_grid[8][8] = 3;
+        let conflicting_board = board_from_rows(conflicting_grid);
+        assert!(!conflicting_board.is_valid());
+        assert_eq!(
+            vec![coordinate(3, 3), coordinate(5, 5)],
+            conflicting_board.all_conflicts()
+        );
+    }
+
+    #[test]
+    fn filled_valid_board_is_complete() {
+        assert!(board_from_rows(SOLVED_GRID).is_complete());
+    }
+
+    #[test]
+    fn filled_invalid_board_is_not_complete() {
+        let mut invalid_grid = SOLVED_GRID;
+        invalid_grid[0][0] = invalid_grid[0][1];
+        assert!(!board_from_rows(invalid_grid).is_complete());
+    }
+
+    #[test]
+    fn partially_filled_valid_board_is_not_complete() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert!(puzzle_board.is_valid());
+        assert!(!puzzle_board.is_complete());
+    }
+
+    #[test]
+    fn parse_board_from_str_grid() {
+        let parsed_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        assert_eq!(PUZZLE_GRID, parsed_board.to_array());
+
+        let zero_padded_puzzle = PUZZLE_STR.replace('.', "0");
+        let zero_padded_board =
+            SudokuBoard::from_str_grid(&zero_padded_puzzle).expect("Zeros are empty cells");
+        assert_eq!(PUZZLE_GRID, zero_padded_board.to_array());
+    }
+
+    #[test]
+    fn parse_str_grid_with_wrong_length_fails() {
+        let ret_err = SudokuBoard::from_str_grid(&PUZZLE_STR[..80]);
+        assert!(matches!(
+            ret_err,
+            Err(ParseError::WrongLength {
+                expected: 81,
+                found: 80
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_str_grid_with_invalid_character_fails() {
+        let invalid_puzzle = PUZZLE_STR.replacen('.', "x", 1);
+        let ret_err = SudokuBoard::from_str_grid(&invalid_puzzle);
+        assert!(matches!(
+            ret_err,
+            Err(ParseError::InvalidCharacter {
+                index: 2,
+                character: 'x',
+                max_value: 9
+            })
+        ));
+    }
+
+    #[test]
+    fn grid_index_round_trips_through_coordinate() {
+        for index in 0..81 {
+            let cell_coordinate =
+                CellCoordinate::from_grid_index(index).expect("Index is in range");
+            assert_eq!(index, cell_coordinate.to_grid_index());
+        }
+    }
+
+    #[test]
+    fn str_grid_round_trips() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert_eq!(PUZZLE_STR, puzzle_board.to_str_grid());
+
+        let reparsed_board = SudokuBoard::from_str_grid(&puzzle_board.to_str_grid())
+            .expect("Serialized board is well formed");
+        assert_eq!(PUZZLE_GRID, reparsed_board.to_array());
+    }
+
+    #[test]
+    fn box_index_of_coordinates() {
+        for ((row, col), expected_box_index) in [
+            ((0, 0), 0),
+            ((2, 2), 0),
+            ((0, 3), 1),
+            ((1, 8), 2),
+            ((4, 4), 4),
+            ((5, 0), 3),
+            ((6, 5), 7),
+            ((8, 8), 8),
+        ] {
+            assert_eq!(expected_box_index, coordinate(row, col).box_index());
+        }
+    }
+
+    #[test]
+    fn rc_coordinates_of_corners_and_center() {
+        let expected_coordinates = [
+            (
+                (0, 0),
+                (Row::Upper, Column::Left),
+                (Row::Upper, Column::Left),
+            ),
+            (
+                (0, 8),
+                (Row::Upper, Column::Right),
+                (Row::Upper, Column::Right),
+            ),
+            (
+                (8, 0),
+                (Row::Bottom, Column::Left),
+                (Row::Bottom, Column::Left),
+            ),
+            (
+                (8, 8),
+                (Row::Bottom, Column::Right),
+                (Row::Bottom, Column::Right),
+            ),
+            (
+                (4, 4),
+                (Row::Center, Column::Center),
+                (Row::Center, Column::Center),
+            ),
+        ];
+        for ((row, col), (sub_grid_row, sub_grid_col), (cell_row, cell_col)) in expected_coordinates
+        {
+            let cell_coordinate = CellCoordinate::from_rc(row, col).expect("Index is in range");
+            assert_eq!(
+                PositionId {
+                    row: sub_grid_row,
+                    column: sub_grid_col
+                },
+                cell_coordinate.sub_grid
+            );
+            assert_eq!(
+                PositionId {
+                    row: cell_row,
+                    column: cell_col
+                },
+                cell_coordinate.cell
+            );
+            assert_eq!((row, col), cell_coordinate.to_rc());
+        }
+    }
+
+    #[test]
+    fn rc_coordinates_out_of_range() {
+        assert_eq!(None, CellCoordinate::from_rc(9, 0));
+        assert_eq!(None, CellCoordinate::from_rc(0, 9));
+        assert_eq!(None, CellCoordinate::from_grid_index(81));
+    }
+
+    #[test]
+    fn overwriting_given_fails() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {
+            row: Row::Upper,
+            column: Column::Right,
+        };
+        let _ = mut_subgrid.set_given(arbitrary_position, 2);
+        assert_eq!(
+            Err(CellError::GivenCell),
+            mut_subgrid.update_value(arbitrary_position, 3)
+        );
+        assert_eq!(
+            Err(CellError::GivenCell),
+            mut_subgrid.clear_value(arbitrary_position)
+        );
+        assert_eq!(Some(2), mut_subgrid.get_value(arbitrary_position));
+    }
+
+    #[test]
+    fn move_on_given_is_rejected() {
+        let mut puzzle_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        assert!(puzzle_board.is_given(coordinate(0, 0)));
+        assert!(!puzzle_board.is_given(coordinate(0, 2)));
+
+        let move_result = puzzle_board.make_move(&SudokuMove {
+            cell_coordinate: coordinate(0, 0),
+            value: 1,
+        });
+        assert!(matches!(
+            move_result,
+            SudokuMoveResult::Rejected(CellError::GivenCell)
+        ));
+        assert_eq!(Some(5), puzzle_board.get_value(coordinate(0, 0)));
+    }
+
+    #[test]
+    fn generated_clues_are_givens() {
+        let puzzle_board = SudokuBoard::generate_with_seed(Difficulty::Easy, 3);
+        for index in 0..81 {
+            let cell_coordinate =
+                CellCoordinate::from_grid_index(index).expect("Index is in range");
+            let is_filled = puzzle_board.get_value(cell_coordinate).is_some();
+            assert_eq!(is_filled, puzzle_board.is_given(cell_coordinate));
+        }
+    }
+
+    #[test]
+    fn locked_cells_refuse_edits_while_empty_cells_stay_editable() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        assert!(!board.is_given(coordinate(0, 0)));
+        board.lock_as_givens();
+        assert!(board.is_given(coordinate(0, 0)));
+        assert!(!board.is_given(coordinate(0, 2)));
+        assert!(matches!(
+            board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(0, 0),
+                value: 1
+            }),
+            SudokuMoveResult::Rejected(CellError::GivenCell)
+        ));
+        assert_eq!(
+            Err(CellError::GivenCell),
+            board.clear_value(coordinate(0, 0))
+        );
+        assert_eq!(Some(5), board.get_value(coordinate(0, 0)));
+        assert!(matches!(
+            board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4
+            }),
+            SudokuMoveResult::Ok { .. }
+        ));
+    }
+
+    #[test]
+    fn toggle_candidate_on_and_off() {
+        let mut board = SudokuBoard::new();
+        let _ = board.toggle_candidate(coordinate(1, 1), 4);
+        let _ = board.toggle_candidate(coordinate(1, 1), 7);
+        assert_eq!(
+            HashSet::from([4, 7]),
+            board.get_candidates(coordinate(1, 1))
+        );
+
+        let _ = board.toggle_candidate(coordinate(1, 1), 4);
+        assert_eq!(HashSet::from([7]), board.get_candidates(coordinate(1, 1)));
+
+        board.clear_candidates(coordinate(1, 1));
+        assert!(board.get_candidates(coordinate(1, 1)).is_empty());
+        assert_eq!(
+            Err(CellError::ValueOutOfRange(0)),
+            board.toggle_candidate(coordinate(1, 1), 0)
+        );
+    }
+
+    #[test]
+    fn toggle_candidates_marks_all_cells_then_unmarks_them() {
+        let mut board = SudokuBoard::new();
+        let _ = board.update_value(coordinate(0, 2), 9);
+        let _ = board.toggle_candidate(coordinate(0, 0), 4);
+        let cells = [coordinate(0, 0), coordinate(0, 1), coordinate(0, 2)];
+
+        assert_eq!(Ok(()), board.toggle_candidates(cells, 4));
+        assert_eq!(HashSet::from([4]), board.get_candidates(coordinate(0, 0)));
+        assert_eq!(HashSet::from([4]), board.get_candidates(coordinate(0, 1)));
+        assert!(board.get_candidates(coordinate(0, 2)).is_empty());
+
+        assert_eq!(Ok(()), board.toggle_candidates(cells, 4));
+        assert!(board.get_candidates(coordinate(0, 0)).is_empty());
+        assert!(board.get_candidates(coordinate(0, 1)).is_empty());
+        assert_eq!(
+            Err(CellError::ValueOutOfRange(10)),
+            board.toggle_candidates(cells, 10)
+        );
+    }
+
+    #[test]
+    fn diagonal_repeat_conflicts_only_under_x_sudoku() {
+        for (variant, expect_conflict) in [(Variant::Classic, false), (Variant::XSudoku, true)] {
+            let mut board = SudokuBoard::with_variant(variant);
+            let _ = board.update_value(coordinate(0, 0), 4);
+            let move_result = board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(4, 4),
+                value: 4,
+            });
+            assert_eq!(
+                expect_conflict,
+                matches!(move_result, SudokuMoveResult::Invalid(_))
+            );
+
+            let _ = board.update_value(coordinate(4, 4), 4);
+            assert_eq!(!expect_conflict, board.is_valid());
+        }
+    }
+
+    #[test]
+    fn solved_x_sudoku_keeps_diagonals_distinct() {
+        let mut board = SudokuBoard::with_variant(Variant::XSudoku);
+        assert!(board.solve());
+        assert!(board.is_complete());
+        let main_diagonal: HashSet<Option<u8>> =
+            (0..9).map(|index| board.get_rc(index, index)).collect();
+        let anti_diagonal: HashSet<Option<u8>> =
+            (0..9).map(|index| board.get_rc(index, 8 - index)).collect();
+        assert_eq!(9, main_diagonal.len());
+        assert_eq!(9, anti_diagonal.len());
+    }
+
+    #[test]
+    fn pruning_removes_placed_value_from_peers_only() {
+        let mut board = SudokuBoard::new();
+        for (row, col) in [(0, 5), (6, 3), (1, 4), (8, 8)] {
+            let _ = board.toggle_candidate(coordinate(row, col), 6);
+            let _ = board.toggle_candidate(coordinate(row, col), 2);
+        }
+        let _ = board.update_value(coordinate(0, 3), 6);
+        board.prune_candidates_after(coordinate(0, 3));
+        for (row, col) in [(0, 5), (6, 3), (1, 4)] {
+            assert_eq!(
+                HashSet::from([2]),
+                board.get_candidates(coordinate(row, col))
+            );
+        }
+        assert_eq!(
+            HashSet::from([2, 6]),
+            board.get_candidates(coordinate(8, 8))
+        );
+
+        board.prune_candidates_after(coordinate(4, 4));
+        assert_eq!(HashSet::from([2]), board.get_candidates(coordinate(1, 4)));
+    }
+
+    #[test]
+    fn setting_value_clears_candidates() {
+        let mut board = SudokuBoard::new();
+        let _ = board.toggle_candidate(coordinate(5, 7), 2);
+        let _ = board.toggle_candidate(coordinate(5, 7), 3);
+        let _ = board.update_value(coordinate(5, 7), 3);
+        assert!(board.get_candidates(coordinate(5, 7)).is_empty());
+    }
+
+    #[test]
+    fn legal_candidates_of_empty_cell() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        // Row 0 holds 5 3 7, column 2 holds 8 and the top left box holds 5 3 6 9 8
+        assert_eq!(
+            HashSet::from([1, 2, 4]),
+            puzzle_board.legal_candidates(&coordinate(0, 2))
+        );
+        // Row 4 holds 4 8 3 1, column 4 holds 7 9 6 2 1 8 and the center box holds 6 8 3 2
+        assert_eq!(
+            HashSet::from([5]),
+            puzzle_board.legal_candidates(&coordinate(4, 4))
+        );
+    }
+
+    #[test]
+    fn legal_candidates_of_filled_cell_are_empty() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert!(puzzle_board.legal_candidates(&coordinate(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn empty_cells_in_reading_order() {
+        let mut board = board_from_rows(SOLVED_GRID);
+        for (row, col) in [(7, 2), (0, 8), (3, 4)] {
+            let _ = board.clear_value(coordinate(row, col));
+        }
+        assert_eq!(
+            vec![coordinate(0, 8), coordinate(3, 4), coordinate(7, 2)],
+            board.empty_cells()
+        );
+    }
+
+    #[test]
+    fn next_empty_after_walks_empties_and_wraps() {
+        let mut board = board_from_rows(SOLVED_GRID);
+        for (row, col) in [(7, 2), (0, 8), (3, 4)] {
+            let _ = board.clear_value(coordinate(row, col));
+        }
+        assert_eq!(
+            Some(coordinate(0, 8)),
+            board.next_empty_after(&coordinate(0, 0))
+        );
+        assert_eq!(
+            Some(coordinate(3, 4)),
+            board.next_empty_after(&coordinate(0, 8))
+        );
+        assert_eq!(
+            Some(coordinate(7, 2)),
+            board.next_empty_after(&coordinate(3, 4))
+        );
+        assert_eq!(
+            Some(coordinate(0, 8)),
+            board.next_empty_after(&coordinate(7, 2))
+        );
+
+        let _ = board.update_value(coordinate(0, 8), 2);
+        let _ = board.update_value(coordinate(3, 4), 6);
+        assert_eq!(None, board.next_empty_after(&coordinate(7, 2)));
+    }
+
+    #[test]
+    fn remaining_count_of_empty_full_and_almost_full_boards() {
+        assert_eq!(81, SudokuBoard::new().remaining_count());
+        assert!(!SudokuBoard::new().is_almost_complete());
+
+        let mut board = board_from_rows(SOLVED_GRID);
+        assert_eq!(0, board.remaining_count());
+        assert!(!board.is_almost_complete());
+
+        let _ = board.clear_value(coordinate(4, 4));
+        assert_eq!(1, board.remaining_count());
+        assert!(board.is_almost_complete());
+    }
+
+    #[test]
+    fn completed_row_is_reported_but_incomplete_row_is_not() {
+        let mut board = SudokuBoard::new();
+        for (col, value) in SOLVED_GRID[0].iter().enumerate() {
+            let _ = board.update_value(coordinate(0, col), *value);
+        }
+        for (col, value) in SOLVED_GRID[1].iter().enumerate().take(8) {
+            let _ = board.update_value(coordinate(1, col), *value);
+        }
+        assert_eq!(vec![UnitId::Row(0)], board.completed_units());
+        assert_eq!(27, board_from_rows(SOLVED_GRID).completed_units().len());
+        assert_eq!(
+            UnitId::Row(0).cells(),
+            board
+                .row_cells(0)
+                .map(|(cell_coordinate, _)| cell_coordinate)
+                .collect::<Vec<_>>()
+        );
+        assert!(UnitId::Box(9).cells().is_empty());
+    }
+
+    #[test]
+    fn parse_errors_display_as_messages() {
+        assert_eq!(
+            "expected 81 cells, found 80",
+            ParseError::WrongLength {
+                expected: 81,
+                found: 80
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "invalid character 'x' at position 3, only 1-9, . and 0 are allowed",
+            ParseError::InvalidCharacter {
+                index: 2,
+                character: 'x',
+                max_value: 9
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "expected 16 cells, found 15",
+            ParseError::WrongLength {
+                expected: 16,
+                found: 15
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "invalid character '5' at position 2, only 1-4, . and 0 are allowed",
+            ParseError::InvalidCharacter {
+                index: 1,
+                character: '5',
+                max_value: 4
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "invalid character 'H' at position 1, only 1-9, A-G, . and 0 are allowed",
+            ParseError::InvalidCharacter {
+                index: 0,
+                character: 'H',
+                max_value: 16
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn conflicting_cells_of_planted_column_conflict() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        assert!(board.conflicting_cells().is_empty());
+        let _ = board.update_value(coordinate(6, 0), 5);
+        assert_eq!(
+            HashSet::from([coordinate(0, 0), coordinate(6, 0)]),
+            board.conflicting_cells()
+        );
+    }
+
+    #[test]
+    fn stats_of_puzzle() {
+        let puzzle_stats = board_from_rows(PUZZLE_GRID).stats();
+        assert_eq!(30, puzzle_stats.filled_cells);
+        assert_eq!(51, puzzle_stats.empty_cells);
+        assert_eq!(5, puzzle_stats.digit_counts[5]);
+        assert!(puzzle_stats.is_valid);
+        assert!(!puzzle_stats.is_complete);
+        assert!(board_from_rows(SOLVED_GRID).stats().is_complete);
+    }
+
+    #[test]
+    fn peers_of_center_and_corner_cells() {
+        let board = SudokuBoard::new();
+        let center_peers = board.peers(&coordinate(4, 4));
+        assert_eq!(20, center_peers.len());
+        assert!(center_peers.contains(&coordinate(4, 0)));
+        assert!(center_peers.contains(&coordinate(3, 5)));
+        assert!(!center_peers.contains(&coordinate(4, 4)));
+        assert!(!center_peers.contains(&coordinate(0, 0)));
+        assert_eq!(20, board.peers(&coordinate(0, 8)).len());
+
+        let x_sudoku_peers = SudokuBoard::with_variant(Variant::XSudoku).peers(&coordinate(4, 4));
+        assert_eq!(32, x_sudoku_peers.len());
+        assert!(x_sudoku_peers.contains(&coordinate(0, 0)));
+    }
+
+    #[test]
+    fn cells_with_value_in_reading_order() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        assert_eq!(
+            vec![coordinate(0, 4), coordinate(5, 0), coordinate(8, 7)],
+            puzzle_board.cells_with_value(7)
+        );
+        assert!(SudokuBoard::new().cells_with_value(7).is_empty());
+    }
+
+    #[test]
+    fn placements_for_digit_on_partial_board() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        let expected_placements = vec![
+            coordinate(0, 3),
+            coordinate(0, 5),
+            coordinate(4, 2),
+            coordinate(7, 6),
+            coordinate(8, 3),
+            coordinate(8, 5),
+            coordinate(8, 6),
+        ];
+        assert_eq!(expected_placements, puzzle_board.placements_for(6));
+        assert!(puzzle_board.placements_for(0).is_empty());
+        assert!(board_from_rows(SOLVED_GRID).placements_for(6).is_empty());
+    }
+
+    #[test]
+    fn mutating_cloned_board_leaves_original_unchanged() {
+        let original_board = board_from_rows(PUZZLE_GRID);
+        let mut cloned_board = original_board.clone();
+        assert!(original_board == cloned_board);
+
+        let _ = cloned_board.update_value(coordinate(0, 2), 4);
+        assert!(original_board != cloned_board);
+        assert_eq!(None, original_board.get_value(coordinate(0, 2)));
+    }
+
+    #[test]
+    fn placed_value_is_not_its_own_row_or_column_duplicate() {
+        let mut board = SudokuBoard::new();
+        let sudoku_move = SudokuMove {
+            cell_coordinate: coordinate(3, 5),
+            value: 8,
+        };
+        assert!(matches!(
+            board.make_move(&sudoku_move),
+            SudokuMoveResult::Ok { cell_coordinate, value: 8 } if cell_coordinate == coordinate(3, 5)
+        ));
+        assert!(board.get_row_duplicates(&sudoku_move).is_none());
+        assert!(board.get_column_duplicates(&sudoku_move).is_none());
+    }
+
+    #[test]
+    fn make_move_reports_each_conflicting_cell_once() {
+        let mut board = SudokuBoard::new();
+        let _ = board.update_value(coordinate(0, 1), 5);
+        let _ = board.update_value(coordinate(1, 0), 5);
+
+        let move_result = board.make_move(&SudokuMove {
+            cell_coordinate: coordinate(0, 0),
+            value: 5,
+        });
+        let SudokuMoveResult::Invalid(conflicts) = move_result else {
+            panic!("Expected SudokuMoveResult::Invalid");
+        };
+        let unique_conflicts: HashSet<_> = conflicts.iter().copied().collect();
+        assert_eq!(conflicts.len(), unique_conflicts.len());
+        assert_eq!(
+            HashSet::from([coordinate(0, 0), coordinate(0, 1), coordinate(1, 0)]),
+            unique_conflicts
+        );
+    }
+
+    #[test]
+    fn equal_coordinates_collapse_in_hash_set() {
+        let coordinates: HashSet<CellCoordinate> =
+            [coordinate(2, 7), coordinate(2, 7)].into_iter().collect();
+        assert_eq!(1, coordinates.len());
+    }
+
+    #[test]
+    fn iter_cells_yields_row_major_order() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        let cells: Vec<_> = puzzle_board.iter_cells().collect();
+        assert_eq!(81, cells.len());
+        for (index, (cell_coordinate, value)) in cells.into_iter().enumerate() {
+            let (row, col) = (index / 9, index % 9);
+            assert_eq!(coordinate(row, col), cell_coordinate);
+            assert_eq!(PUZZLE_GRID[row][col], value.unwrap_or(0));
+        }
+    }
+
+    #[test]
+    fn seeded_generation_and_solving_are_unchanged() {
+        // Output captured before the board moved from hash maps to fixed arrays
+        let mut board = SudokuBoard::generate_with_seed(Difficulty::Hard, 2024);
+        assert_eq!(
+            "..1..2.73.8..4...6......2...2...43....9.2...15..8...2.1.8..7..2..24..1....3.6.4.7",
+            board.to_str_grid()
+        );
+        assert!(board.solve());
+        assert_eq!(
+            "461592873287143596395678214726914358839725641514836729148357962672489135953261487",
+            board.to_str_grid()
+        );
+    }
+
+    #[test]
+    fn heuristic_solver_matches_plain_solver() {
+        for puzzle in [
+            PUZZLE_STR,
+            "..1..2.73.8..4...6......2...2...43....9.2...15..8...2.1.8..7..2..24..1....3.6.4.7",
+        ] {
+            let mut plain_board =
+                SudokuBoard::from_str_grid(puzzle).expect("Puzzle string is well formed");
+            let mut heuristic_board = plain_board.clone();
+            assert!(plain_board.solve());
+            assert!(heuristic_board.solve_with_heuristic());
+            assert!(heuristic_board.is_complete());
+            assert_eq!(plain_board, heuristic_board);
+        }
+
+        let mut invalid_board = board_from_rows(PUZZLE_GRID);
+        let _ = invalid_board.update_value(coordinate(0, 2), 5);
+        assert!(!invalid_board.solve_with_heuristic());
+    }
+
+    #[test]
+    fn validate_puzzle_accepts_unique_puzzle() {
+        let puzzle_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        assert_eq!(Ok(()), puzzle_board.validate_puzzle());
+        assert_eq!(
+            Err(PuzzleError::NotUnique),
+            SudokuBoard::new().validate_puzzle()
+        );
+    }
+
+    #[test]
+    fn validate_puzzle_reports_conflicting_givens() {
+        let mut conflicting_puzzle = PUZZLE_STR.to_string();
+        conflicting_puzzle.replace_range(2..3, "5");
+        let board =
+            SudokuBoard::from_str_grid(&conflicting_puzzle).expect("Puzzle string is well formed");
+        let Err(PuzzleError::InitialConflict(conflicts)) = board.validate_puzzle() else {
+            panic!("Two 5s in the first row must conflict");
+        };
+        assert!(conflicts.contains(&coordinate(0, 0)));
+        assert!(conflicts.contains(&coordinate(0, 2)));
+    }
+
+    #[test]
+    fn validate_puzzle_reports_unsolvable_puzzle_without_conflicts() {
+        // The top right cell can only take a 9, but the column already holds one
+        let mut unsolvable_puzzle = "12345678.........9".to_string();
+        unsolvable_puzzle.push_str(&".".repeat(63));
+        let board =
+            SudokuBoard::from_str_grid(&unsolvable_puzzle).expect("Puzzle string is well formed");
+        assert!(board.is_valid());
+        assert_eq!(Err(PuzzleError::Unsolvable), board.validate_puzzle());
+    }
+
+    #[test]
+    fn clear_empties_every_cell() {
+        let mut board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let _ = board.update_value(coordinate(0, 2), 4);
+        let _ = board.toggle_candidate(coordinate(8, 0), 3);
+        board.clear();
+        assert!(board.iter_cells().all(|(_, value)| value.is_none()));
+        assert!(!board.is_given(coordinate(0, 0)));
+        assert!(board.get_candidates(coordinate(8, 0)).is_empty());
+    }
+
+    #[test]
+    fn digit_placed_nine_times_is_completed() {
+        let mut rows = [[0_u8; 9]; 9];
+        for (row, solved_row) in SOLVED_GRID.iter().enumerate() {
+            for (col, value) in solved_row.iter().enumerate() {
+                if *value == 5 {
+                    rows[row][col] = 5;
+                }
+            }
+        }
+        rows[0][1] = 3;
+        let board = board_from_rows(rows);
+        assert_eq!(vec![5], board.completed_digits());
+    }
+
+    #[test]
+    fn remaining_counts_of_puzzle() {
+        let board = board_from_rows(PUZZLE_GRID);
+        assert_eq!([6, 7, 6, 7, 6, 4, 6, 4, 5], board.remaining_counts());
+        assert_eq!([0; 9], board_from_rows(SOLVED_GRID).remaining_counts());
+    }
+
+    #[test]
+    fn display_renders_ascii_grid() {
+        let board = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let expected = "\
++---+---+---+
+|53.|.7.|...|
+|6..|195|...|
+|.98|...|.6.|
++---+---+---+
+|8..|.6.|..3|
+|4..|8.3|..1|
+|7..|.2.|..6|
++---+---+---+
+|.6.|...|28.|
+|...|419|..5|
+|...|.8.|.79|
++---+---+---+";
+        assert_eq!(expected, board.to_string());
+    }
+
+    #[test]
+    fn naked_singles_solve_easy_puzzle() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        assert_eq!(51, board.fill_naked_singles());
+        assert!(board.is_complete());
+        assert_eq!(SOLVED_GRID, board.to_array());
+    }
+
+    #[test]
+    fn hidden_singles_progress_where_naked_singles_stall() {
+        let mut board = SudokuBoard::from_str_grid(
+            ".5..8.129.1....48..6.2..5...89...2.442..5.7..6....489......39..1957..34.....1.6..",
+        )
+        .expect("Puzzle string is well formed");
+        assert_eq!(0, board.fill_naked_singles());
+
+        assert!(board.fill_hidden_singles() > 0);
+        while board.fill_naked_singles() + board.fill_hidden_singles() > 0 {}
+        assert!(board.is_complete());
+    }
+
+    #[test]
+    fn rate_puzzles_by_technique() {
+        let easy = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let medium = SudokuBoard::from_str_grid(
+            ".5..8.129.1....48..6.2..5...89...2.442..5.7..6....489......39..1957..34.....1.6..",
+        )
+        .expect("Puzzle string is well formed");
+        let hard = SudokuBoard::from_str_grid(
+            ".......5.8...6....564.9.8.3.2...71.5....18..2.4...6978...65...7.9....2.1...17....",
+        )
+        .expect("Puzzle string is well formed");
+        assert_eq!(Difficulty::Easy, easy.rate());
+        assert_eq!(Difficulty::Medium, medium.rate());
+        assert_eq!(Difficulty::Hard, hard.rate());
+    }
+
+    #[test]
+    fn solution_of_unique_puzzle() {
+        let board = board_from_rows(PUZZLE_GRID);
+        let solved_board = board.solution().expect("Puzzle has a unique solution");
+        assert_eq!(SOLVED_GRID, solved_board.to_array());
+        assert_eq!(PUZZLE_GRID, board.to_array());
+    }
+
+    #[test]
+    fn solution_of_ambiguous_or_unsolvable_grid_is_none() {
+        let mut rows = PUZZLE_GRID;
+        rows[0] = [0; 9];
+        rows[1] = [0; 9];
+        rows[2] = [0; 9];
+        assert!(board_from_rows(rows).solution().is_none());
+        assert!(SudokuBoard::new().solution().is_none());
+
+        let mut unsolvable = board_from_rows(PUZZLE_GRID);
+        let _ = unsolvable.update_value(coordinate(0, 2), 5);
+        assert!(unsolvable.solution().is_none());
+    }
+
+    #[test]
+    fn column_conflict_is_reported_under_its_column() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        let _ = board.update_value(coordinate(6, 0), 5);
+        assert_eq!(
+            vec![(UnitId::Column(0), vec![coordinate(0, 0), coordinate(6, 0)])],
+            board.conflicts_by_unit()
+        );
+    }
+
+    #[test]
+    fn get_and_set_by_row_and_column() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        assert_eq!(Some(5), board.get_rc(0, 0));
+        assert_eq!(None, board.get_rc(0, 2));
+        assert!(matches!(board.set_rc(0, 2, 4), SudokuMoveResult::Ok { .. }));
+        assert_eq!(Some(4), board.get_rc(0, 2));
+        assert!(matches!(
+            board.set_rc(8, 0, 9),
+            SudokuMoveResult::Invalid(_)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "row 9 and column 0 must both be in 0-8")]
+    fn get_rc_out_of_range_panics() {
+        SudokuBoard::new().get_rc(9, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 0 and column 9 must both be in 0-8")]
+    fn set_rc_out_of_range_panics() {
+        SudokuBoard::new().set_rc(0, 9, 1);
+    }
+
+    #[test]
+    fn apply_moves_reports_the_conflicting_move() {
+        let moves = [
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 3),
+                value: 5,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 7),
+                value: 1,
+            },
+        ];
+
+        let mut board = board_from_rows(PUZZLE_GRID);
+        let move_results = board.apply_moves(&moves, false);
+        assert_eq!(3, move_results.len());
+        assert!(matches!(move_results[0], (0, SudokuMoveResult::Ok { .. })));
+        assert!(matches!(move_results[1], (1, SudokuMoveResult::Invalid(_))));
+        assert!(matches!(move_results[2], (2, SudokuMoveResult::Ok { .. })));
+        assert_eq!(Some(1), board.get_value(coordinate(0, 7)));
+
+        let mut board = board_from_rows(PUZZLE_GRID);
+        let move_results = board.apply_moves(&moves, true);
+        assert_eq!(2, move_results.len());
+        assert!(matches!(move_results[1], (1, SudokuMoveResult::Invalid(_))));
+        assert_eq!(None, board.get_value(coordinate(0, 7)));
+    }
+
+    #[test]
+    fn partial_board_is_consistent_with_its_solution() {
+        let solved_board = board_from_rows(SOLVED_GRID);
+        let mut board = board_from_rows(PUZZLE_GRID);
+        assert!(board.is_consistent_with(&solved_board));
+
+        let _ = board.update_value(coordinate(0, 2), 4);
+        assert!(board.is_consistent_with(&solved_board));
+
+        let _ = board.update_value(coordinate(0, 3), 2);
+        assert!(!board.is_consistent_with(&solved_board));
+    }
+
+    #[test]
+    fn sdk_file_round_trips() {
+        let board = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let sdk_path = std::env::temp_dir().join(format!("sudoku_rs_{}.sdk", std::process::id()));
+        board.to_file(&sdk_path).expect("Temp dir is writable");
+        let contents = fs::read_to_string(&sdk_path).expect("File was just written");
+        let loaded_board = SudokuBoard::from_file(&sdk_path).expect("File was just written");
+        let _ = fs::remove_file(&sdk_path);
+
+        assert_eq!(
+            "53..7....",
+            contents.lines().next().expect("File has nine lines")
+        );
+        assert_eq!(9, contents.lines().count());
+        assert!(board == loaded_board);
+    }
+
+    #[test]
+    fn malformed_sdk_file_fails() {
+        let sdk_path =
+            std::env::temp_dir().join(format!("sudoku_rs_bad_{}.sdk", std::process::id()));
+        let mut lines: Vec<String> = (0..9)
+            .map(|row| PUZZLE_STR[row * 9..(row + 1) * 9].to_string())
+            .collect();
+        lines[4] = "4..8x3..1".to_string();
+        fs::write(&sdk_path, lines.join("\n")).expect("Temp dir is writable");
+        let bad_character = SudokuBoard::from_file(&sdk_path);
+        lines.pop();
+        fs::write(&sdk_path, lines.join("\n")).expect("Temp dir is writable");
+        let missing_line = SudokuBoard::from_file(&sdk_path);
+        let _ = fs::remove_file(&sdk_path);
+
+        assert!(matches!(
+            bad_character,
+            Err(FileError::Line {
+                line: 4,
+                error: ParseError::InvalidCharacter {
+                    index: 4,
+                    character: 'x',
+                    max_value: 9
+                }
+            })
+        ));
+        assert!(matches!(missing_line, Err(FileError::WrongLineCount(8))));
+    }
+
+    #[test]
+    fn symmetric_puzzle_keeps_rotational_partners() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let board = SudokuBoard::generate(Difficulty::Hard, true, &mut rng);
+        for row in 0..9 {
+            for col in 0..9 {
+                assert_eq!(
+                    board.is_given(coordinate(row, col)),
+                    board.is_given(coordinate(8 - row, 8 - col)),
+                    "Cell ({}, {}) and its partner differ",
+                    row,
+                    col
+                );
+            }
+        }
+        assert_eq!(1, board.count_solutions(2));
+    }
+
+    #[test]
+    fn legal_but_wrong_move_differs_from_duplicate() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        board.set_reference_solution(Some(board_from_rows(SOLVED_GRID)));
+
+        let wrong_move = SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 2,
+        };
+        assert!(
+            matches!(board.make_move(&wrong_move), SudokuMoveResult::WrongValue(cell) if cell == coordinate(0, 2))
+        );
+        let duplicate_move = SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 5,
+        };
+        assert!(matches!(
+            board.make_move(&duplicate_move),
+            SudokuMoveResult::Invalid(_)
+        ));
+        let correct_move = SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 4,
+        };
+        assert!(matches!(
+            board.make_move(&correct_move),
+            SudokuMoveResult::Ok { .. }
+        ));
+
+        board.set_reference_solution(None);
+        assert!(matches!(
+            board.make_move(&wrong_move),
+            SudokuMoveResult::Ok { .. }
+        ));
+    }
+
+    #[test]
+    fn unit_iterators_yield_their_nine_cells() {
+        let board = board_from_rows(PUZZLE_GRID);
+
+        let row: Vec<_> = board.row_cells(4).collect();
+        assert_eq!(
+            (0..9).map(|col| coordinate(4, col)).collect::<Vec<_>>(),
+            row.iter().map(|(cell, _)| *cell).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Some(4),
+                None,
+                None,
+                Some(8),
+                None,
+                Some(3),
+                None,
+                None,
+                Some(1)
+            ],
+            row.iter().map(|(_, value)| *value).collect::<Vec<_>>()
+        );
+
+        let column: Vec<_> = board.column_cells(2).map(|(cell, _)| cell).collect();
+        assert_eq!(
+            (0..9).map(|row| coordinate(row, 2)).collect::<Vec<_>>(),
+            column
+        );
+
+        let box_cells: Vec<_> = board.box_cells(5).map(|(cell, _)| cell).collect();
+        assert_eq!(9, box_cells.len());
+        assert_eq!(coordinate(3, 6), box_cells[0]);
+        assert_eq!(coordinate(4, 8), box_cells[5]);
+        assert_eq!(coordinate(5, 8), box_cells[8]);
+        assert!(box_cells
+            .iter()
+            .all(|cell| cell.sub_grid == coordinate(4, 7).sub_grid));
+
+        assert_eq!(0, board.row_cells(9).count());
+        assert_eq!(0, board.box_cells(9).count());
+    }
+
+    #[test]
+    fn shared_helper_finds_duplicates_in_each_unit_type() {
+        let mut board = SudokuBoard::new();
+        // Row 0 repeats 1, column 8 repeats 2 and the center box repeats 3
+        let _ = board.update_value(coordinate(0, 0), 1);
+        let _ = board.update_value(coordinate(0, 6), 1);
+        let _ = board.update_value(coordinate(2, 8), 2);
+        let _ = board.update_value(coordinate(7, 8), 2);
+        let _ = board.update_value(coordinate(3, 3), 3);
+        let _ = board.update_value(coordinate(5, 5), 3);
+
+        assert_eq!(
+            vec![(coordinate(0, 0), 1), (coordinate(0, 6), 1)],
+            duplicates_in_unit(board.row_cells(0))
+        );
+        assert_eq!(
+            vec![(coordinate(2, 8), 2), (coordinate(7, 8), 2)],
+            duplicates_in_unit(board.column_cells(8))
+        );
+        assert_eq!(
+            vec![(coordinate(3, 3), 3), (coordinate(5, 5), 3)],
+            duplicates_in_unit(board.box_cells(4))
+        );
+        assert!(duplicates_in_unit(board.row_cells(3)).is_empty());
+    }
+
+    #[test]
+    fn make_move_reports_conflicts_alike_in_every_unit_type() {
+        for partner in [coordinate(4, 0), coordinate(0, 4), coordinate(5, 5)] {
+            let mut board = SudokuBoard::new();
+            let _ = board.update_value(partner, 7);
+            let move_result = board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(4, 4),
+                value: 7,
+            });
+            let SudokuMoveResult::Invalid(conflicts) = move_result else {
+                panic!("Expected SudokuMoveResult::Invalid");
+            };
+            assert_eq!(
+                HashSet::from([coordinate(4, 4), partner]),
+                conflicts.into_iter().collect()
+            );
+        }
+    }
+
+    #[test]
+    fn would_conflict_leaves_board_unchanged() {
+        let board = board_from_rows(PUZZLE_GRID);
+        let original_board = board.clone();
+        // 9 is already in row 2 at (2, 1), and in column 4 and the top middle box at (1, 4)
+        let conflicts = board.would_conflict(&SudokuMove {
+            cell_coordinate: coordinate(2, 4),
+            value: 9,
+        });
+        assert!(board == original_board);
+        assert_eq!(None, board.get_value(coordinate(2, 4)));
+        assert_eq!(
+            HashSet::from([coordinate(1, 4), coordinate(2, 1)]),
+            conflicts.iter().copied().collect()
+        );
+        assert_eq!(2, conflicts.len());
+
+        assert!(board
+            .would_conflict(&SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4
+            })
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_move_leaves_cell_at_previous_value() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        let _ = board.update_value(coordinate(0, 2), 4);
+        let move_result = board.make_move(&SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 5,
+        });
+        assert!(matches!(move_result, SudokuMoveResult::Invalid(_)));
+        assert_eq!(Some(4), board.get_value(coordinate(0, 2)));
+
+        let move_result = board.make_move(&SudokuMove {
+            cell_coordinate: coordinate(0, 3),
+            value: 3,
+        });
+        assert!(matches!(move_result, SudokuMoveResult::Invalid(_)));
+        assert_eq!(None, board.get_value(coordinate(0, 3)));
+    }
+
+    #[test]
+    fn invalid_move_leaves_subgrid_untouched() {
+        let mut mut_subgrid = SubGrid::new();
+        let arbitrary_position = PositionId {
+            row: Row::Upper,
+            column: Column::Left,
+        };
+        let arbitrary_position_2 = PositionId {
+            row: Row::Bottom,
+            column: Column::Right,
+        };
+        let _ = mut_subgrid.make_move(SubGridMove {
+            cell: arbitrary_position,
+            value: 4,
+        });
+        let _ = mut_subgrid.update_value(arbitrary_position_2, 2);
+        let move_result = mut_subgrid.make_move(SubGridMove {
+            cell: arbitrary_position_2,
+            value: 4,
+        });
+        assert_eq!(
+            SubgridMoveResult::Invalid(vec![arbitrary_position, arbitrary_position_2]),
+            move_result
+        );
+        assert_eq!(Some(2), mut_subgrid.get_value(arbitrary_position_2));
+    }
+
+    #[test]
+    fn debug_output_has_one_labelled_line_per_row() {
+        let debug_output = format!("{:?}", board_from_rows(PUZZLE_GRID));
+        let lines: Vec<&str> = debug_output.lines().collect();
+        assert_eq!(9, lines.len());
+        assert_eq!("R1: 5 3 . | . 7 . | . . .", lines[0]);
+        assert_eq!("R9: . . . | . 8 . | . 7 9", lines[8]);
+    }
+
+    #[test]
+    fn rotating_four_times_returns_original_board() {
+        let mut puzzle_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let _ = puzzle_board.update_value(coordinate(0, 2), 4);
+        let _ = puzzle_board.toggle_candidate(coordinate(8, 0), 3);
+        let rotated_board = puzzle_board.rotate_90();
+        assert_eq!(Some(5), rotated_board.get_rc(0, 8));
+        assert_eq!(Some(4), rotated_board.get_rc(2, 8));
+        assert!(rotated_board.is_given(coordinate(0, 8)));
+        assert!(!rotated_board.is_given(coordinate(2, 8)));
+        assert_eq!(
+            HashSet::from([3]),
+            rotated_board.get_candidates(coordinate(0, 0))
+        );
+        assert_eq!(
+            puzzle_board,
+            rotated_board.rotate_90().rotate_90().rotate_90()
+        );
+    }
+
+    #[test]
+    fn reflections_mirror_the_board_and_undo_themselves() {
+        let puzzle_board = board_from_rows(PUZZLE_GRID);
+        let mut upside_down_rows = PUZZLE_GRID;
+        upside_down_rows.reverse();
+        assert_eq!(
+            upside_down_rows,
+            puzzle_board.reflect_horizontal().to_array()
+        );
+        assert_eq!(Some(5), puzzle_board.reflect_vertical().get_rc(0, 8));
+        assert_eq!(
+            puzzle_board,
+            puzzle_board.reflect_horizontal().reflect_horizontal()
+        );
+        assert_eq!(
+            puzzle_board,
+            puzzle_board.reflect_vertical().reflect_vertical()
+        );
+    }
+
+    #[test]
+    fn relabel_with_identity_and_inverse_mappings() {
+        let puzzle_board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        assert_eq!(
+            puzzle_board,
+            puzzle_board.relabel([1, 2, 3, 4, 5, 6, 7, 8, 9])
+        );
+
+        let mapping = [4, 9, 1, 7, 2, 8, 3, 6, 5];
+        let mut inverse_mapping = [0_u8; 9];
+        for (index, value) in mapping.iter().enumerate() {
+            inverse_mapping[*value as usize - 1] = index as u8 + 1;
+        }
+        let relabeled_board = puzzle_board.relabel(mapping);
+        assert_eq!(Some(2), relabeled_board.get_rc(0, 0));
+        assert!(relabeled_board.is_valid());
+        assert_eq!(puzzle_board, relabeled_board.relabel(inverse_mapping));
+    }
+
+    #[test]
+    #[should_panic(expected = "must hold each of 1-9 once")]
+    fn relabel_rejects_mapping_that_is_not_a_permutation() {
+        let _ = SudokuBoard::new().relabel([1, 1, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn position_id_serializes_as_key() {
+        let position_id = PositionId {
+            row: Row::Upper,
+            column: Column::Center,
+        };
+        assert_eq!(
+            "\"R1C2\"",
+            serde_json::to_string(&position_id).expect("Position serializes")
+        );
+        assert_eq!(
+            position_id,
+            serde_json::from_str("\"R1C2\"").expect("Key parses")
+        );
+        assert_eq!(
+            position_id,
+            serde_json::from_str(r#"{"row":"Upper","column":"Center"}"#)
+                .expect("Legacy form parses")
+        );
+        for invalid_key in ["\"R0C1\"", "\"R1C4\"", "\"C1R1\"", "\"R1C1 \""] {
+            assert!(serde_json::from_str::<PositionId>(invalid_key).is_err());
+        }
+    }
+
+    #[test]
+    fn board_round_trips_through_json() {
+        let mut board =
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let _ = board.update_value(coordinate(0, 2), 4);
+        let _ = board.toggle_candidate(coordinate(8, 0), 3);
+        board.set_reference_solution(Some(board_from_rows(SOLVED_GRID)));
+        let json = serde_json::to_string(&board).expect("Board serializes");
+        let loaded_board: SudokuBoard = serde_json::from_str(&json).expect("Board deserializes");
+        assert_eq!(board, loaded_board);
+
+        let sudoku_move = SudokuMove {
+            cell_coordinate: coordinate(4, 5),
+            value: 7,
+        };
+        let move_json = serde_json::to_string(&sudoku_move).expect("Move serializes");
+        assert_eq!(
+            r#"{"cell_coordinate":{"sub_grid":"R2C2","cell":"R2C3"},"value":7}"#,
+            move_json
+        );
+    }
+
+    #[test]
+    fn csv_with_wrong_dimensions_or_fields_fails() {
+        let csv_rows = ["5,3,,,7,,,,"; 9];
+        assert!(matches!(
+            SudokuBoard::from_csv(&csv_rows[..8].join("\n")),
+            Err(FileError::WrongLineCount(8))
+        ));
+
+        let mut short_rows = csv_rows;
+        short_rows[4] = "5,3,,,7,,,";
+        assert!(matches!(
+            SudokuBoard::from_csv(&short_rows.join("\n")),
+            Err(FileError::Line {
+                line: 4,
+                error: ParseError::WrongLength {
+                    expected: 9,
+                    found: 8
+                }
+            })
+        ));
+
+        let mut invalid_rows = csv_rows;
+        invalid_rows[2] = "5,3,,12,7,,,,";
+        assert!(matches!(
+            SudokuBoard::from_csv(&invalid_rows.join("\n")),
+            Err(FileError::Line {
+                line: 2,
+                error: ParseError::InvalidCharacter {
+                    index: 3,
+                    character: '2',
+                    max_value: 9
+                }
+            })
+        ));
+
+        invalid_rows[2] = "5,3,,x,7,,,,";
+        assert!(matches!(
+            SudokuBoard::from_csv(&invalid_rows.join("\n")),
+            Err(FileError::Line {
+                line: 2,
+                error: ParseError::InvalidCharacter {
+                    index: 3,
+                    character: 'x',
+                    max_value: 9
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn overlay_fills_empty_cells_and_keeps_user_entries() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        board
+            .update_value(coordinate(0, 2), 1)
+            .expect("Cell is empty and not a given");
+        board
+            .update_value(coordinate(4, 4), 2)
+            .expect("Cell is empty and not a given");
+
+        board.overlay(&board_from_rows(SOLVED_GRID));
+        assert_eq!(Some(1), board.get_value(coordinate(0, 2)));
+        assert_eq!(Some(2), board.get_value(coordinate(4, 4)));
+        assert!(!board.is_given(coordinate(0, 3)));
+        let mut expected_rows = SOLVED_GRID;
+        expected_rows[0][2] = 1;
+        expected_rows[4][4] = 2;
+        assert_eq!(expected_rows, board.to_array());
+    }
+
+    #[test]
+    fn make_move_fast_matches_make_move_verdict() {
+        let mut board = board_from_rows(PUZZLE_GRID);
+        board.set_reference_solution(Some(board_from_rows(SOLVED_GRID)));
+        let moves = [
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 1,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 5,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 9,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 0),
+                value: 1,
+            },
+            SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 0,
+            },
+        ];
+        for (index, sudoku_move) in moves.iter().enumerate() {
+            let mut fast_board = board.clone();
+            let mut full_board = board.clone();
+            let played = fast_board.make_move_fast(sudoku_move);
+            let result = full_board.make_move(sudoku_move);
+            assert_eq!(
+                matches!(
+                    result,
+                    SudokuMoveResult::Ok { .. } | SudokuMoveResult::WrongValue(_)
+                ),
+                played,
+                "move {}",
+                index
+            );
+            assert_eq!(full_board.to_array(), fast_board.to_array());
+        }
+    }
+
+    #[test]
+    fn flat_board_round_trips_with_sudoku_board() {
+        let board = board_from_rows(PUZZLE_GRID);
+        let flat_board = FlatBoard::from(&board);
+        assert_eq!(Some(5), flat_board.0[0]);
+        assert_eq!(None, flat_board.0[2]);
+        assert_eq!(Some(9), flat_board.0[80]);
+        assert_eq!(
+            PUZZLE_GRID,
+            SudokuBoard::try_from(flat_board)
+                .expect("Flat board only holds values up to 9")
+                .to_array()
+        );
+
+        let mut out_of_range = flat_board;
+        out_of_range.0[2] = Some(10);
+        assert!(matches!(
+            SudokuBoard::try_from(out_of_range),
+            Err(CellError::ValueOutOfRange(10))
+        ));
+    }
+
+    #[test]
+    fn flat_board_serializes_as_list_of_81_cells() {
+        let flat_board = FlatBoard::from(&board_from_rows(SOLVED_GRID));
+        let json = serde_json::to_string(&flat_board).expect("Flat board serializes");
+        assert!(json.starts_with("[5,3,4,6,"));
+        assert_eq!(
+            flat_board,
+            serde_json::from_str(&json).expect("Json was just written")
+        );
+
+        let short_json = format!("[{}]", ["null"; 80].join(","));
+        assert!(serde_json::from_str::<FlatBoard>(&short_json).is_err());
+    }
+
+    #[test]
+    fn next_logical_step_finds_naked_single_first() {
+        let board = board_from_rows(PUZZLE_GRID);
+        let step = board
+            .next_logical_step()
+            .expect("Puzzle has a naked single");
+        assert_eq!(Technique::NakedSingle, step.technique);
+        assert_eq!(coordinate(4, 4), step.cell_coordinate);
+        assert_eq!(5, step.value);
+        assert_eq!(PUZZLE_GRID, board.to_array());
+
+        let stalled_board = SudokuBoard::from_str_grid(
+            ".5..8.129.1....48..6.2..5...89...2.442..5.7..6....489......39..1957..34.....1.6..",
+        )
+        .expect("Puzzle string is well formed");
+        let step = stalled_board
+            .next_logical_step()
+            .expect("Puzzle has a hidden single");
+        assert_eq!(Technique::HiddenSingle, step.technique);
+        let solved_board = stalled_board
+            .solution()
+            .expect("Puzzle has a unique solution");
+        assert_eq!(
+            solved_board.get_value(step.cell_coordinate),
+            Some(step.value)
+        );
+
+        assert_eq!(None, board_from_rows(SOLVED_GRID).next_logical_step());
+    }
+
+    #[test]
+    fn sub_grids_are_iterated_in_row_major_order() {
+        let board = board_from_rows(SOLVED_GRID);
+        let position_ids: Vec<PositionId> = board
+            .sub_grids_ordered()
+            .map(|(position_id, _)| position_id)
+            .collect();
+        let expected_ids: Vec<PositionId> = [
+            "R1C1", "R1C2", "R1C3", "R2C1", "R2C2", "R2C3", "R3C1", "R3C2", "R3C3",
+        ]
+        .into_iter()
+        .map(|key| PositionId::from_key(key).expect("Keys name valid sub-grids"))
+        .collect();
+        assert_eq!(expected_ids, position_ids);
+
+        // Each sub-grid is the one stored at its position: the centre holds rows 3-5, columns 3-5
+        let (_, center) = board
+            .sub_grids_ordered()
+            .nth(4)
+            .expect("Board has nine sub-grids");
+        assert_eq!(
+            Some(7),
+            center.get_value(PositionId {
+                row: Row::Upper,
+                column: Column::Left
+            })
+        );
+        assert_eq!(
+            Some(4),
+            center.get_value(PositionId {
+                row: Row::Bottom,
+                column: Column::Right
+            })
+        );
+    }
+
+    #[test]
+    fn from_array_places_literal_values() {
+        let board = SudokuBoard::from_array([
+            [5, 3, 0, 0, 7, 0, 0, 0, 0],
+            [6, 0, 0, 1, 9, 5, 0, 0, 0],
+            [0, 9, 8, 0, 0, 0, 0, 6, 0],
+            [8, 0, 0, 0, 6, 0, 0, 0, 3],
+            [4, 0, 0, 8, 0, 3, 0, 0, 1],
+            [7, 0, 0, 0, 2, 0, 0, 0, 6],
+            [0, 6, 0, 0, 0, 0, 2, 8, 0],
+            [0, 0, 0, 4, 1, 9, 0, 0, 5],
+            [0, 0, 0, 0, 8, 0, 0, 7, 9],
+        ])
+        .expect("Test grids only hold values up to 9");
+        assert_eq!(Some(5), board.get_value(coordinate(0, 0)));
+        assert_eq!(Some(3), board.get_value(coordinate(4, 5)));
+        assert_eq!(None, board.get_value(coordinate(8, 0)));
+        assert!(!board.is_given(coordinate(0, 0)));
+
+        let mut out_of_range = SOLVED_GRID;
+        out_of_range[8][8] = 10;
+        assert!(matches!(
+            SudokuBoard::from_array(out_of_range),
+            Err(CellError::ValueOutOfRange(10))
+        ));
+    }
+
+    #[test]
+    fn to_array_round_trips_through_from_array() {
+        let board = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+        let grid = board.to_array();
+        assert_eq!(PUZZLE_GRID, grid);
+        assert_eq!(0, grid[0][2]);
+        assert_eq!(9, grid[8][8]);
+        let round_tripped_board =
+            SudokuBoard::from_array(grid).expect("Test grids only hold values up to 9");
+        assert_eq!(grid, round_tripped_board.to_array());
+        assert_eq!(PUZZLE_STR, round_tripped_board.to_str_grid());
+    }
+
+    struct DiscardingLogger;
+
+    impl log::Log for DiscardingLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        // Formats the record so the logged arguments are exercised, then drops it
+        fn log(&self, record: &log::Record) {
+            let _ = record.args().to_string();
+        }
+
+        fn flush(&self) {}
+    }
+
+    static DISCARDING_LOGGER: DiscardingLogger = DiscardingLogger;
+
+    #[test]
+    fn moves_play_the_same_with_a_logger_installed() {
+        // Another test may have installed it already, the logger is process-wide
+        let _ = log::set_logger(&DISCARDING_LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let mut board = board_from_rows(PUZZLE_GRID);
+        board.lock_as_givens();
+        assert!(matches!(
+            board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4
+            }),
+            SudokuMoveResult::Ok { value: 4, .. }
+        ));
+        assert!(matches!(
+            board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(0, 3),
+                value: 5
+            }),
+            SudokuMoveResult::Invalid(_)
+        ));
+        assert!(matches!(
+            board.make_move(&SudokuMove {
+                cell_coordinate: coordinate(0, 0),
+                value: 1
+            }),
+            SudokuMoveResult::Rejected(CellError::GivenCell)
+        ));
+        assert_eq!(Some(4), board.get_value(coordinate(0, 2)));
+        assert_eq!(None, board.get_value(coordinate(0, 3)));
+    }
+}
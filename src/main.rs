@@ -1,524 +1,1910 @@
-use std::{cell::Cell, collections::HashMap};
+use std::{
+    collections::HashSet,
+    env, fmt, fs,
+    io::{self, Read},
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
 
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+use sudoku_rs::{
+    CellCoordinate, Difficulty, LogicalStep, ParseError, PuzzleError, SudokuBoard, SudokuMove,
+    SudokuMoveResult,
+};
 
-fn keys_with_duplicate_values<K: Eq + std::hash::Hash + Clone, V: Eq + std::hash::Hash>(
-    map: &HashMap<K, Option<V>>,
-) -> Vec<K> {
-    let mut value_counts: HashMap<&V, usize> = HashMap::new();
-
-    // Count occurrences of each value
-    for option_value in map.values() {
-        if let Some(value) = option_value {
-            *value_counts.entry(value).or_insert(0) += 1;
-        }
-    }
-    
-    // Collect keys where Some(value) appears more than once
-    map.iter()
-    .filter(|(_, value)| match value {
-        Some(v) => value_counts.get(&v).unwrap_or(&0) > &1, // Fix: use &v for lookup
-        None => false,
-    })
-    .map(|(key, _)| key.clone())
-    .collect()
-}
+#[cfg(test)]
+mod test_fixtures;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Row {
-    Upper = 1,
-    Center = 2,
-    Bottom = 3,
+// Side length of a single cell in points, the grid and the window scale with it
+const CELL_SIZE: f32 = 48.0;
+const GRID_SIZE: f32 = CELL_SIZE * 9.0;
+const WINDOW_MARGIN: f32 = 64.0;
+const STATS_PANEL_WIDTH: f32 = 120.0;
+const CELL_LINE_WIDTH: f32 = 1.0;
+const SUB_GRID_LINE_WIDTH: f32 = 3.0;
+// Font family givens are drawn with, registered in `install_fonts`
+const GIVEN_FONT: &str = "FiraSans-Bold";
+
+/// Colors the board is drawn with, kept apart from the game state
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Theme {
+    name: &'static str,
+    // Whether the rest of the window should use egui's dark visuals
+    dark: bool,
+    background: egui::Color32,
+    given: egui::Color32,
+    user_entry: egui::Color32,
+    // Digits filled in from the solution one cell at a time
+    revealed_entry: egui::Color32,
+    candidate: egui::Color32,
+    conflict: egui::Color32,
+    wrong_entry: egui::Color32,
+    selected: egui::Color32,
+    // Cells the digit hovered on the number pad could go in
+    placement: egui::Color32,
+    // Briefly shown on a row, column or sub-grid a move just completed
+    completed: egui::Color32,
+    // Other cells holding the digit of the selected cell
+    same_value: egui::Color32,
+    // Cells sharing a row, column or sub-grid with the selected cell
+    peer: egui::Color32,
+    grid_line: egui::Color32,
 }
 
-impl Row {
-    fn all() -> &'static [Row] {
-        &[Row::Upper, Row::Center, Row::Bottom]
+impl Theme {
+    fn light() -> Self {
+        Self {
+            name: "Light",
+            dark: false,
+            background: egui::Color32::from_rgb(250, 250, 245),
+            given: egui::Color32::from_rgb(20, 20, 20),
+            user_entry: egui::Color32::from_rgb(60, 110, 200),
+            revealed_entry: egui::Color32::from_rgb(140, 80, 180),
+            candidate: egui::Color32::from_rgb(120, 120, 120),
+            conflict: egui::Color32::from_rgb(230, 80, 80),
+            wrong_entry: egui::Color32::from_rgb(240, 170, 60),
+            selected: egui::Color32::from_rgb(190, 215, 245),
+            placement: egui::Color32::from_rgb(205, 235, 200),
+            completed: egui::Color32::from_rgb(250, 235, 150),
+            same_value: egui::Color32::from_rgb(215, 225, 240),
+            peer: egui::Color32::from_rgb(238, 240, 245),
+            grid_line: egui::Color32::from_rgb(20, 20, 20),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            name: "Dark",
+            dark: true,
+            background: egui::Color32::from_rgb(30, 32, 36),
+            given: egui::Color32::from_rgb(230, 230, 230),
+            user_entry: egui::Color32::from_rgb(120, 170, 250),
+            revealed_entry: egui::Color32::from_rgb(190, 140, 230),
+            candidate: egui::Color32::from_rgb(140, 140, 140),
+            conflict: egui::Color32::from_rgb(150, 50, 50),
+            wrong_entry: egui::Color32::from_rgb(160, 110, 30),
+            selected: egui::Color32::from_rgb(50, 70, 100),
+            placement: egui::Color32::from_rgb(45, 80, 55),
+            completed: egui::Color32::from_rgb(110, 95, 30),
+            same_value: egui::Color32::from_rgb(55, 60, 75),
+            peer: egui::Color32::from_rgb(40, 43, 50),
+            grid_line: egui::Color32::from_rgb(200, 200, 200),
+        }
+    }
+
+    fn visuals(&self) -> egui::Visuals {
+        if self.dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Column {
-    Left = 1,
-    Center = 2,
-    Right = 3,
+/// A move applied to the board along with the value it overwrote, so it can be undone
+#[derive(Serialize, Deserialize)]
+struct HistoryEntry {
+    sudoku_move: SudokuMove,
+    previous: Option<u8>,
+    // Undoing a move that counted as a mistake takes the mistake back.
+    // Missing from saves written before mistakes were tracked per move
+    #[serde(default)]
+    counted_as_mistake: bool,
+    // Time into the game the move was made, zero for moves from older saves
+    #[serde(default)]
+    elapsed: Duration,
 }
 
-impl Column {
-    fn all() -> &'static [Column] {
-        &[Column::Left, Column::Center, Column::Right]
+const DIGIT_KEYS: [(egui::Key, u8); 9] = [
+    (egui::Key::Num1, 1),
+    (egui::Key::Num2, 2),
+    (egui::Key::Num3, 3),
+    (egui::Key::Num4, 4),
+    (egui::Key::Num5, 5),
+    (egui::Key::Num6, 6),
+    (egui::Key::Num7, 7),
+    (egui::Key::Num8, 8),
+    (egui::Key::Num9, 9),
+];
+
+/// What typing a digit does: Pen places it, Pencil toggles it as a candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputMode {
+    Pen,
+    Pencil,
+}
+
+impl InputMode {
+    fn toggled(self) -> Self {
+        match self {
+            InputMode::Pen => InputMode::Pencil,
+            InputMode::Pencil => InputMode::Pen,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct PositionId {
-    row: Row,
-    column: Column,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
-/// Represents a 3x3 section of the Sudoku board
-#[derive(Default, Clone)]
-struct SubGrid {
-    cells: HashMap<PositionId, Option<u8>>,
+const ARROW_KEYS: [(egui::Key, Direction); 4] = [
+    (egui::Key::ArrowUp, Direction::Up),
+    (egui::Key::ArrowDown, Direction::Down),
+    (egui::Key::ArrowLeft, Direction::Left),
+    (egui::Key::ArrowRight, Direction::Right),
+];
+
+// How long the units completed by a move stay highlighted
+const COMPLETED_FLASH_DURATION: Duration = Duration::from_millis(800);
+// Mistakes allowed before the game is over
+const DEFAULT_MAX_MISTAKES: u8 = 3;
+// Score deductions, see `SudokuApp::score`
+const SCORE_PER_MISTAKE: u32 = 100;
+const SCORE_PER_HINT: u32 = 200;
+// Where the Save and Load buttons keep the game
+const SAVE_FILE_PATH: &str = "sudoku_save.json";
+
+/// Game state written to disk, the timer is kept as the time spent so far
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    board: SudokuBoard,
+    move_history: Vec<HistoryEntry>,
+    nr_mistakes: u8,
+    max_mistakes: Option<u8>,
+    hints_used: u8,
+    elapsed: Duration,
+    finished: bool,
+    #[serde(default)]
+    revealed: bool,
+    // Missing from saves written before solutions were stored
+    #[serde(default)]
+    solution: Option<SudokuBoard>,
+}
+
+#[derive(Debug)]
+enum SaveError {
+    Io(io::Error),
+    Json(serde_json::Error),
 }
 
-struct SubGridMove {
-    cell: PositionId,
-    value: u8,
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "{}", error),
+            SaveError::Json(error) => write!(f, "invalid save file, {}", error),
+        }
+    }
 }
 
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Json(error)
+    }
+}
+
+/// Why a pasted puzzle wasn't loaded
 #[derive(Debug, PartialEq)]
-enum SubgridMoveResult {
-    Ok,
-    Invalid(Vec<PositionId>),
+enum LoadPuzzleError {
+    Parse(ParseError),
+    Puzzle(PuzzleError),
 }
 
-impl SubGrid {
-    /// Creates a new empty 3x3 subgrid
+impl fmt::Display for LoadPuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadPuzzleError::Parse(error) => write!(f, "{}", error),
+            LoadPuzzleError::Puzzle(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<ParseError> for LoadPuzzleError {
+    fn from(error: ParseError) -> Self {
+        LoadPuzzleError::Parse(error)
+    }
+}
+
+impl From<PuzzleError> for LoadPuzzleError {
+    fn from(error: PuzzleError) -> Self {
+        LoadPuzzleError::Puzzle(error)
+    }
+}
+
+struct SudokuApp {
+    board: SudokuBoard,
+    move_history: Vec<HistoryEntry>,
+    nr_mistakes: u8,
+    // None plays without a mistake limit
+    max_mistakes: Option<u8>,
+    hints_used: u8,
+    started_at: Instant,
+    // Set once the board is complete, which stops the timer
+    finished_in: Option<Duration>,
+    // The player gave up and the solution was filled in, no further input is taken
+    revealed: bool,
+    // Several cells can be selected to pencil mark them together
+    selected: HashSet<CellCoordinate>,
+    // Cells reported by the last move that turned out invalid
+    conflicts: Vec<CellCoordinate>,
+    // Outcome of the last save or load, shown below the board
+    save_status: Option<String>,
+    // Solution of the puzzle as it was started, None when it has no unique solution
+    solution: Option<SudokuBoard>,
+    // Entries the last check found to differ from the solution
+    wrong_entries: Vec<CellCoordinate>,
+    // Cells filled by revealing them one at a time, until they're edited again
+    revealed_cells: HashSet<CellCoordinate>,
+    theme: Theme,
+    // Placing a digit removes it from the pencil marks of the cell's peers
+    auto_prune_candidates: bool,
+    // Strict mode: a move breaking a rule is kept off the board, otherwise it's placed and its
+    // conflicts highlighted. Either way it counts as a mistake
+    reject_conflicting_input: bool,
+    input_mode: InputMode,
+    // Digit under the pointer on the number pad, its placements are highlighted
+    hovered_digit: Option<u8>,
+    // Cells of the units the last move completed, highlighted until the instant passes
+    completed_flash: Option<(Vec<CellCoordinate>, Instant)>,
+    // Text of the paste box and why the last pasted puzzle didn't load
+    puzzle_input: String,
+    puzzle_input_error: Option<String>,
+    // Deduction pointed out by the last guided hint, dropped by the next move
+    guided_step: Option<LogicalStep>,
+}
+
+impl SudokuApp {
     fn new() -> Self {
-        let mut empty_cells: HashMap<PositionId, Option<u8>> = HashMap::new();
-        for row in Row::all() {
-            for column in Column::all() {
-                let position_id = PositionId {
-                    row: *row,
-                    column: *column,
-                };
-                empty_cells.insert(position_id, None);
-            }
+        Self::with_board(SudokuBoard::generate(
+            Difficulty::Easy,
+            true,
+            &mut rand::thread_rng(),
+        ))
+    }
+
+    fn with_board(board: SudokuBoard) -> Self {
+        let solution = board.solution();
+        Self {
+            board,
+            move_history: Vec::new(),
+            nr_mistakes: 0_u8,
+            max_mistakes: Some(DEFAULT_MAX_MISTAKES),
+            hints_used: 0_u8,
+            started_at: Instant::now(),
+            finished_in: None,
+            revealed: false,
+            selected: HashSet::new(),
+            conflicts: Vec::new(),
+            save_status: None,
+            solution,
+            wrong_entries: Vec::new(),
+            revealed_cells: HashSet::new(),
+            theme: Theme::light(),
+            auto_prune_candidates: false,
+            reject_conflicting_input: true,
+            input_mode: InputMode::Pen,
+            hovered_digit: None,
+            completed_flash: None,
+            puzzle_input: String::new(),
+            puzzle_input_error: None,
+            guided_step: None,
         }
+    }
 
-        Self { cells: empty_cells }
+    /// Writes the board, history, mistakes and elapsed time to a JSON file
+    fn save_to_path(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let saved_game = SavedGame {
+            board: self.board.clone(),
+            move_history: self
+                .move_history
+                .iter()
+                .map(|history_entry| HistoryEntry {
+                    sudoku_move: SudokuMove {
+                        cell_coordinate: history_entry.sudoku_move.cell_coordinate,
+                        value: history_entry.sudoku_move.value,
+                    },
+                    previous: history_entry.previous,
+                    counted_as_mistake: history_entry.counted_as_mistake,
+                    elapsed: history_entry.elapsed,
+                })
+                .collect(),
+            nr_mistakes: self.nr_mistakes,
+            max_mistakes: self.max_mistakes,
+            hints_used: self.hints_used,
+            elapsed: self.elapsed(),
+            finished: self.finished_in.is_some(),
+            revealed: self.revealed,
+            solution: self.solution.clone(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&saved_game)?)?;
+        Ok(())
     }
 
-    fn update_value(&mut self, key: PositionId, value: u8) -> Result< () , String> {
-        if value < 10 {
-            if let Some(entry) = self.cells.get_mut(&key) {
-                *entry = Some(value); // Only updates existing keys
-            }
-            Ok(())
-        }else{
-            Err("Invalid cell value".to_string())
+    /// Resumes a game written by `save_to_path`, the timer continues from the saved time
+    fn load_from_path(path: impl AsRef<Path>) -> Result<SudokuApp, SaveError> {
+        let saved_game: SavedGame = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut app = Self::with_board(saved_game.board);
+        app.move_history = saved_game.move_history;
+        app.nr_mistakes = saved_game.nr_mistakes;
+        app.max_mistakes = saved_game.max_mistakes;
+        app.hints_used = saved_game.hints_used;
+        app.started_at = Instant::now()
+            .checked_sub(saved_game.elapsed)
+            .unwrap_or_else(Instant::now);
+        if saved_game.finished {
+            app.finished_in = Some(saved_game.elapsed);
+        }
+        app.revealed = saved_game.revealed;
+        if saved_game.solution.is_some() {
+            app.solution = saved_game.solution;
         }
+        Ok(app)
     }
 
-    fn get_value(&self, key: PositionId) -> Option<u8> {
-        self.cells.get(&key).copied()?
+    /// Empties the board and starts over with no history, mistakes or hints and a fresh timer
+    fn new_game(&mut self) {
+        self.board.clear();
+        self.move_history.clear();
+        self.nr_mistakes = 0;
+        self.hints_used = 0;
+        self.selected.clear();
+        self.conflicts.clear();
+        self.solution = None;
+        self.wrong_entries.clear();
+        self.revealed_cells.clear();
+        self.revealed = false;
+        self.guided_step = None;
+        self.completed_flash = None;
+        self.puzzle_input_error = None;
+        self.reset_timer();
     }
 
-    // Returns vec with all the positions where there is a duplicate
-    fn get_duplicates(&self) -> Option<Vec<PositionId>> {
-        let duplicates = keys_with_duplicate_values(&self.cells);
-        if duplicates.len() > 0 {
-            Some(duplicates)
-        } else {
-            None
+    /// Starts the same puzzle over: every entry and pencil mark is cleared but the givens stay,
+    /// and history, mistakes, hints and the timer are reset
+    fn restart(&mut self) {
+        let cells: Vec<CellCoordinate> = self
+            .board
+            .iter_cells()
+            .map(|(cell_coordinate, _)| cell_coordinate)
+            .collect();
+        for cell_coordinate in cells {
+            if !self.board.is_given(cell_coordinate) {
+                let _ = self.board.clear_value(cell_coordinate);
+                self.board.clear_candidates(cell_coordinate);
+            }
         }
+        self.move_history.clear();
+        self.nr_mistakes = 0;
+        self.hints_used = 0;
+        self.selected.clear();
+        self.conflicts.clear();
+        self.wrong_entries.clear();
+        self.revealed_cells.clear();
+        self.revealed = false;
+        self.completed_flash = None;
+        self.guided_step = None;
+        self.reset_timer();
     }
 
-    pub fn make_move(&mut self, sub_grid_move: SubGridMove) -> SubgridMoveResult {
-        let mut invalid_cells = Vec::new();
+    /// Gives up on the puzzle: fills every cell with its solution value and stops the game
+    /// without counting it as solved. Does nothing when the solution isn't known
+    fn reveal_solution(&mut self) {
+        let Some(solution) = &self.solution else {
+            return;
+        };
+        for (cell_coordinate, value) in solution.iter_cells() {
+            if let Some(value) = value {
+                // Givens already hold their solution value and refuse the write
+                let _ = self.board.update_value(cell_coordinate, value);
+            }
+        }
+        self.revealed = true;
+        self.selected.clear();
+        self.conflicts.clear();
+        self.wrong_entries.clear();
+        if self.finished_in.is_none() {
+            self.finished_in = Some(self.started_at.elapsed());
+        }
+    }
 
-        if let Some(_entry) = self.cells.get(&sub_grid_move.cell) {
-            self.update_value(sub_grid_move.cell, sub_grid_move.value);
-            if let Some(duplicates) = self.get_duplicates() {
-                invalid_cells.extend(duplicates);
-            };
+    /// Targeted hint: fills the cell with its solution value and marks it as revealed. Does
+    /// nothing when no solution is stored or the cell already holds the right value
+    fn reveal_cell(&mut self, cell_coordinate: CellCoordinate) {
+        if self.revealed {
+            return;
+        }
+        let Some(value) = self
+            .solution
+            .as_ref()
+            .and_then(|solution| solution.get_value(cell_coordinate))
+        else {
+            return;
+        };
+        if self.board.get_value(cell_coordinate) == Some(value) {
+            return;
         }
+        // Givens hold their solution value, so the cell is editable and the value is 1-9
+        let _ = self.board.update_value(cell_coordinate, value);
+        self.board.clear_candidates(cell_coordinate);
+        self.revealed_cells.insert(cell_coordinate);
+        self.hints_used = self.hints_used.saturating_add(1);
+        self.wrong_entries
+            .retain(|wrong_entry| *wrong_entry != cell_coordinate);
+        self.refresh_conflicts();
+        if self.finished_in.is_none() && self.board.is_complete() {
+            self.finished_in = Some(self.started_at.elapsed());
+        }
+    }
 
-        if invalid_cells.len() > 0 {
-            SubgridMoveResult::Invalid(invalid_cells)
-        } else {
-            SubgridMoveResult::Ok
+    /// Difficulty of the puzzle as it was given, judged on the givens alone
+    fn puzzle_difficulty(&self) -> Difficulty {
+        let mut puzzle = SudokuBoard::new();
+        for (cell_coordinate, value) in self.board.iter_cells() {
+            if let (Some(value), true) = (value, self.board.is_given(cell_coordinate)) {
+                let _ = puzzle.update_value(cell_coordinate, value);
+            }
         }
+        puzzle.rate()
     }
-}
 
-struct CellCoordinate {
-    sub_grid: PositionId,
-    cell: PositionId,
-}
+    /// Points for the game, shown once it's solved. The puzzle's difficulty sets the base
+    /// (Easy 1000, Medium 2000, Hard 3000), then every second taken costs a point up to half the
+    /// base, every mistake 100 and every hint 200. The score never drops below 0, and a revealed
+    /// solution scores 0
+    fn score(&self) -> u32 {
+        if self.revealed {
+            return 0;
+        }
+        let base: u32 = match self.puzzle_difficulty() {
+            Difficulty::Easy => 1000,
+            Difficulty::Medium => 2000,
+            // `rate` tops out at Hard
+            Difficulty::Hard | Difficulty::Expert => 3000,
+        };
+        let elapsed_seconds = u32::try_from(self.elapsed().as_secs()).unwrap_or(u32::MAX);
+        let time_penalty = elapsed_seconds.min(base / 2);
+        let penalties = time_penalty
+            + u32::from(self.nr_mistakes) * SCORE_PER_MISTAKE
+            + u32::from(self.hints_used) * SCORE_PER_HINT;
+        base.saturating_sub(penalties)
+    }
 
-/// Represents the full 9x9 Sudoku board
-#[derive(Default)]
-struct SudokuBoard {
-    sub_grids: HashMap<PositionId, SubGrid>,
-}
+    /// Turns the digits entered so far into the givens of a new puzzle, e.g. one typed in on an
+    /// empty board, and starts playing it with a fresh history, mistake count and timer
+    fn lock_givens(&mut self) {
+        let mut board = self.board.clone();
+        board.lock_as_givens();
+        self.replace_game(Self::with_board(board));
+    }
 
-struct SudokuMove {
-    cell_coordinate: CellCoordinate,
-    value: u8,
-}
+    // Switches to another game, keeping the player's preferences, which aren't part of a game
+    fn replace_game(&mut self, app: SudokuApp) {
+        *self = SudokuApp {
+            theme: self.theme,
+            auto_prune_candidates: self.auto_prune_candidates,
+            reject_conflicting_input: self.reject_conflicting_input,
+            input_mode: self.input_mode,
+            ..app
+        };
+    }
 
-enum SudokuMoveResult {
-    Ok,
-    Invalid(Vec<CellCoordinate>),
-}
+    /// Starts a new game on a pasted 81-character puzzle, its digits become the givens.
+    /// Whitespace and line breaks are ignored. The game is left alone when the puzzle doesn't
+    /// parse, repeats a digit in a unit or hasn't exactly one solution
+    fn load_puzzle_str(&mut self, input: &str) -> Result<(), LoadPuzzleError> {
+        let puzzle: String = input
+            .chars()
+            .filter(|character| !character.is_whitespace())
+            .collect();
+        let board = SudokuBoard::from_str_grid(&puzzle)?;
+        board.validate_puzzle()?;
+        self.replace_game(Self::with_board(board));
+        Ok(())
+    }
 
-impl SudokuBoard {
-    /// Creates an empty Sudoku board
-    fn new() -> Self {
-        let mut sub_grids: HashMap<PositionId, SubGrid> = HashMap::new();
-        for row in Row::all() {
-            for col in Column::all() {
-                sub_grids.insert(
-                    PositionId {
-                        row: *row,
-                        column: *col,
-                    },
-                    SubGrid::new(),
-                );
+    /// Compares every entry made by the player with the solution and returns the ones that
+    /// differ, even when they break no rule yet. Finds nothing when the solution isn't known
+    fn check(&mut self) -> Vec<CellCoordinate> {
+        self.wrong_entries = match &self.solution {
+            Some(solution) => self
+                .board
+                .iter_cells()
+                .filter(|(cell_coordinate, value)| {
+                    value.is_some()
+                        && !self.board.is_given(*cell_coordinate)
+                        && *value != solution.get_value(*cell_coordinate)
+                })
+                .map(|(cell_coordinate, _)| cell_coordinate)
+                .collect(),
+            None => Vec::new(),
+        };
+        self.wrong_entries.clone()
+    }
+
+    fn mistakes(&self) -> u8 {
+        self.nr_mistakes
+    }
+
+    /// Time spent on the puzzle, frozen once it's solved
+    fn elapsed(&self) -> Duration {
+        self.finished_in
+            .unwrap_or_else(|| self.started_at.elapsed())
+    }
+
+    /// Restarts the timer, for when a new puzzle is started
+    fn reset_timer(&mut self) {
+        self.started_at = Instant::now();
+        self.finished_in = None;
+    }
+
+    /// True once the mistakes reached the configured limit
+    fn is_game_over(&self) -> bool {
+        self.max_mistakes
+            .is_some_and(|max_mistakes| self.nr_mistakes >= max_mistakes)
+    }
+
+    /// Applies the move to the board and records it in the history unless it was rejected.
+    /// Invalid and wrong moves count as a mistake
+    fn make_move(&mut self, sudoku_move: SudokuMove) -> SudokuMoveResult {
+        self.guided_step = None;
+        let previous = self.board.get_value(sudoku_move.cell_coordinate);
+        let completed_before = self.board.completed_units();
+        let move_result = self.board.make_move(&sudoku_move);
+        if !self.reject_conflicting_input && matches!(move_result, SudokuMoveResult::Invalid(_)) {
+            // The board refuses conflicting values, place it directly so the conflict shows
+            let _ = self
+                .board
+                .update_value(sudoku_move.cell_coordinate, sudoku_move.value);
+        }
+        let newly_completed: Vec<CellCoordinate> = self
+            .board
+            .completed_units()
+            .into_iter()
+            .filter(|unit_id| !completed_before.contains(unit_id))
+            .flat_map(|unit_id| unit_id.cells())
+            .collect();
+        if self.auto_prune_candidates
+            && matches!(
+                move_result,
+                SudokuMoveResult::Ok { .. } | SudokuMoveResult::WrongValue(_)
+            )
+        {
+            self.board
+                .prune_candidates_after(sudoku_move.cell_coordinate);
+        }
+        if !newly_completed.is_empty() {
+            self.completed_flash =
+                Some((newly_completed, Instant::now() + COMPLETED_FLASH_DURATION));
+        }
+        self.wrong_entries
+            .retain(|cell_coordinate| *cell_coordinate != sudoku_move.cell_coordinate);
+        self.revealed_cells.remove(&sudoku_move.cell_coordinate);
+        let counted_as_mistake = matches!(
+            move_result,
+            SudokuMoveResult::Invalid(_) | SudokuMoveResult::WrongValue(_)
+        );
+        if counted_as_mistake {
+            self.nr_mistakes = self.nr_mistakes.saturating_add(1);
+        }
+        if !matches!(move_result, SudokuMoveResult::Rejected(_)) {
+            self.move_history.push(HistoryEntry {
+                sudoku_move,
+                previous,
+                counted_as_mistake,
+                elapsed: self.elapsed(),
+            });
+        }
+        if self.finished_in.is_none() && self.board.is_complete() {
+            self.finished_in = Some(self.started_at.elapsed());
+        }
+        move_result
+    }
+
+    /// Fills the selected cell, or the first empty one, with its value from the puzzle's solution.
+    /// Returns the filled cell, or None when the board is complete, has no unique solution or the
+    /// game is over
+    fn hint(&mut self) -> Option<CellCoordinate> {
+        if self.is_game_over() || self.revealed {
+            return None;
+        }
+        let cell_coordinate = self
+            .single_selected()
+            .filter(|selected| self.board.get_value(*selected).is_none())
+            .or_else(|| self.board.empty_cells().first().copied())?;
+        // The stored solution still holds after a wrong entry, solving the board only works
+        // while every entry is right
+        let value = match &self.solution {
+            Some(solution) => solution.get_value(cell_coordinate),
+            None => self.board.solution()?.get_value(cell_coordinate),
+        }?;
+
+        // Written directly like a revealed cell: a wrong entry in one of its units can make the
+        // right digit look like a conflict, which must neither keep it off the board nor count
+        // as a mistake. The cell is empty and not a given, so the write succeeds
+        self.guided_step = None;
+        let _ = self.board.update_value(cell_coordinate, value);
+        self.board.clear_candidates(cell_coordinate);
+        self.move_history.push(HistoryEntry {
+            sudoku_move: SudokuMove {
+                cell_coordinate,
+                value,
+            },
+            previous: None,
+            counted_as_mistake: false,
+            elapsed: self.elapsed(),
+        });
+        self.hints_used = self.hints_used.saturating_add(1);
+        self.refresh_conflicts();
+        if self.finished_in.is_none() && self.board.is_complete() {
+            self.finished_in = Some(self.started_at.elapsed());
+        }
+        Some(cell_coordinate)
+    }
+
+    /// Guided hint: selects the cell of the easiest deduction available and keeps the step so
+    /// its technique can be explained, the cell is left for the player to fill. Returns the
+    /// step, None when only harder techniques would progress
+    fn show_next_step(&mut self) -> Option<LogicalStep> {
+        if self.revealed {
+            return None;
+        }
+        self.guided_step = self.board.next_logical_step();
+        if let Some(step) = self.guided_step {
+            self.selected = HashSet::from([step.cell_coordinate]);
+        }
+        self.guided_step
+    }
+
+    /// Reverts the last recorded move, restoring the value its cell held before and taking back
+    /// the mistake it counted as
+    fn undo(&mut self) {
+        if self.revealed {
+            return;
+        }
+        let Some(history_entry) = self.move_history.pop() else {
+            return;
+        };
+        if history_entry.counted_as_mistake {
+            self.nr_mistakes = self.nr_mistakes.saturating_sub(1);
+        }
+        let cell_coordinate = history_entry.sudoku_move.cell_coordinate;
+        match history_entry.previous {
+            Some(previous_value) => {
+                // The previous value was read from the board, so writing it back cannot fail
+                let _ = self.board.update_value(cell_coordinate, previous_value);
+            }
+            None => {
+                let _ = self.board.clear_value(cell_coordinate);
             }
         }
+        self.wrong_entries
+            .retain(|wrong_entry| *wrong_entry != cell_coordinate);
+        self.revealed_cells.remove(&cell_coordinate);
+        self.refresh_conflicts();
+    }
 
-        Self {
-            sub_grids: sub_grids,
+    // The selected cell when exactly one is selected
+    fn single_selected(&self) -> Option<CellCoordinate> {
+        match self.selected.len() {
+            1 => self.selected.iter().next().copied(),
+            _ => None,
         }
     }
 
-    fn update_value(&mut self, cell_coordinate: CellCoordinate, value: u8) {
-        if let Some(subgrid_entry) = self.sub_grids.get_mut(&cell_coordinate.sub_grid) {
-            subgrid_entry.update_value(cell_coordinate.cell, value);
+    /// Enters the digit into the selected cell. Digits typed with no cell or several cells
+    /// selected, once the game is over or after the solution was revealed are ignored
+    fn enter_digit(&mut self, value: u8) {
+        if self.is_game_over() || self.revealed {
+            return;
+        }
+        let Some(cell_coordinate) = self.single_selected() else {
+            return;
+        };
+        match self.make_move(SudokuMove {
+            cell_coordinate,
+            value,
+        }) {
+            SudokuMoveResult::Invalid(conflicts) => self.conflicts = conflicts,
+            SudokuMoveResult::Ok { .. }
+            | SudokuMoveResult::Rejected(_)
+            | SudokuMoveResult::WrongValue(_) => self.conflicts.clear(),
         }
     }
 
-    fn get_value(&self, cell_coordinate: CellCoordinate) -> Option<u8> {
-        let sub_grid = self.sub_grids.get(&cell_coordinate.sub_grid)?;
-        sub_grid.get_value(cell_coordinate.cell)
+    /// Selects the next empty cell after the first selected one in reading order, or the first
+    /// empty cell when nothing is selected. The selection is left alone on a full board
+    fn select_next_empty(&mut self) {
+        let start = self
+            .selected
+            .iter()
+            .min_by_key(|selected| selected.to_grid_index())
+            .copied();
+        let next_empty = match start {
+            Some(start) => self.board.next_empty_after(&start),
+            None => self.board.empty_cells().first().copied(),
+        };
+        if let Some(next_empty) = next_empty {
+            self.selected = HashSet::from([next_empty]);
+        }
     }
 
-    fn get_row_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
-        let mut row_duplicates = Vec::new();
-        for sub_grid_col in Column::all() {
-            let sub_grid_pos = PositionId {
-                row: sudoku_move.cell_coordinate.sub_grid.row,
-                column: *sub_grid_col,
-            };
-            if let Some(subgrid_entry) = self.sub_grids.get(&sub_grid_pos) {
-                for cell_col in Column::all() {
-                    let cell_pos = PositionId {
-                        row: sudoku_move.cell_coordinate.cell.row,
-                        column: *cell_col,
-                    };
-                    if let Some(value) = subgrid_entry.get_value(cell_pos) {
-                        if value == sudoku_move.value {
-                            row_duplicates.push(CellCoordinate {
-                                sub_grid: sub_grid_pos,
-                                cell: cell_pos,
-                            });
-                        }
-                    }
-                }
-            }
+    /// Places the digit in Pen mode and pencil marks it in Pencil mode
+    fn apply_digit(&mut self, value: u8, input_mode: InputMode) {
+        match input_mode {
+            InputMode::Pen => self.enter_digit(value),
+            InputMode::Pencil => self.toggle_selected_candidates(value),
         }
+    }
 
-        if row_duplicates.len() > 0 {
-            Some(row_duplicates)
-        } else {
-            None
+    /// Toggles the candidate in every selected cell
+    fn toggle_selected_candidates(&mut self, value: u8) {
+        if self.revealed {
+            return;
         }
+        let _ = self
+            .board
+            .toggle_candidates(self.selected.iter().copied(), value);
     }
 
-    fn get_column_duplicates(&self, sudoku_move: &SudokuMove) -> Option<Vec<CellCoordinate>> {
-        let mut col_duplicates = Vec::new();
-        for sub_grid_row in Row::all() {
-            let sub_grid_pos = PositionId {
-                row: *sub_grid_row,
-                column: sudoku_move.cell_coordinate.sub_grid.column,
-            };
-            if let Some(subgrid_entry) = self.sub_grids.get(&sub_grid_pos) {
-                for cell_row in Row::all() {
-                    let cell_pos = PositionId {
-                        row: *cell_row,
-                        column: sudoku_move.cell_coordinate.cell.column,
-                    };
-                    if let Some(value) = subgrid_entry.get_value(cell_pos) {
-                        if value == sudoku_move.value {
-                            col_duplicates.push(CellCoordinate {
-                                sub_grid: sub_grid_pos,
-                                cell: cell_pos,
-                            });
-                        }
-                    }
-                }
+    fn erase_selected(&mut self) {
+        if self.revealed {
+            return;
+        }
+        for cell_coordinate in self.selected.clone() {
+            if self.board.clear_value(cell_coordinate).is_ok() {
+                self.wrong_entries
+                    .retain(|wrong_entry| *wrong_entry != cell_coordinate);
+                self.revealed_cells.remove(&cell_coordinate);
             }
         }
+        self.refresh_conflicts();
+    }
 
-        if col_duplicates.len() > 0 {
-            Some(col_duplicates)
-        } else {
-            None
+    // Drops the highlight from cells that no longer conflict after a clear or undo
+    fn refresh_conflicts(&mut self) {
+        let remaining_conflicts = self.board.conflicting_cells();
+        self.conflicts
+            .retain(|cell_coordinate| remaining_conflicts.contains(cell_coordinate));
+    }
+
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        // Keys typed into the paste box are meant for it, not the board
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        if ctx.input(|input| input.key_pressed(egui::Key::Space)) {
+            self.input_mode = self.input_mode.toggled();
+        }
+        // Tab jumps to the next empty cell, taken before egui uses it to move widget focus
+        if ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            self.select_next_empty();
+        }
+        let (pressed_digit, command_held) = ctx.input(|input| {
+            let pressed_digit = DIGIT_KEYS
+                .iter()
+                .find(|(key, _)| input.key_pressed(*key))
+                .map(|(_, value)| *value);
+            (pressed_digit, input.modifiers.command)
+        });
+        // Ctrl (Cmd on macOS) with a digit uses the other mode for that one digit
+        match pressed_digit {
+            Some(value) if command_held => self.apply_digit(value, self.input_mode.toggled()),
+            Some(value) => self.apply_digit(value, self.input_mode),
+            None => {}
+        }
+        let pressed_direction = ctx.input(|input| {
+            ARROW_KEYS
+                .iter()
+                .find(|(key, _)| input.key_pressed(*key))
+                .map(|(_, direction)| *direction)
+        });
+        if let Some(direction) = pressed_direction {
+            // The arrows step from the first selected cell in reading order and collapse the
+            // selection onto the new cell. With nothing selected they start from the top left
+            let next_cell = match self
+                .selected
+                .iter()
+                .min_by_key(|selected| selected.to_grid_index())
+            {
+                Some(selected) => step_selection(*selected, direction),
+                None => CellCoordinate::from_rc(0, 0).expect("Top left cell is in range"),
+            };
+            self.selected = HashSet::from([next_cell]);
+        }
+        if ctx.input(|input| {
+            input.key_pressed(egui::Key::Backspace) || input.key_pressed(egui::Key::Delete)
+        }) {
+            self.erase_selected();
         }
     }
 
-    //
-    fn make_move(&mut self, sudoku_move: &SudokuMove) -> SudokuMoveResult {
-        let mut invalid_cells_coordinates = Vec::new();
+    fn update_menu(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("Options", |ui| {
+                    ui.checkbox(&mut self.auto_prune_candidates, "Auto-remove pencil marks");
+                    ui.checkbox(
+                        &mut self.reject_conflicting_input,
+                        "Reject conflicting digits",
+                    );
+                });
+                ui.menu_button("Theme", |ui| {
+                    for theme in [Theme::light(), Theme::dark()] {
+                        if ui
+                            .selectable_label(self.theme == theme, theme.name)
+                            .clicked()
+                        {
+                            self.theme = theme;
+                            ctx.set_visuals(theme.visuals());
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+        });
+    }
 
-        // Adds all duplicate cells in the sub-grid where the move was attempted ->
-        if let Some(subgrid_entry) = self.sub_grids.get_mut(&sudoku_move.cell_coordinate.sub_grid) {
-            if let SubgridMoveResult::Invalid(invalid_cells) =
-                subgrid_entry.make_move(SubGridMove {
-                    cell: sudoku_move.cell_coordinate.cell,
-                    value: sudoku_move.value,
-                })
-            {
-                for invalid_cell in invalid_cells.iter() {
-                    invalid_cells_coordinates.push(CellCoordinate {
-                        sub_grid: sudoku_move.cell_coordinate.sub_grid,
-                        cell: *invalid_cell,
+    fn update_stats(&self, ctx: &egui::Context) {
+        let board_stats = self.board.stats();
+        egui::SidePanel::right("stats")
+            .exact_width(STATS_PANEL_WIDTH)
+            .show(ctx, |ui| {
+                ui.heading("Stats");
+                ui.label(format!("Filled: {}", board_stats.filled_cells));
+                ui.label(format!("Empty: {}", board_stats.empty_cells));
+                for (value, digit_count) in (1..=9).zip(board_stats.digit_counts) {
+                    ui.label(format!("{}: {}/9", value, digit_count));
+                }
+                if !board_stats.is_valid {
+                    ui.label("Board has conflicts");
+                }
+            });
+    }
+
+    fn update_grid(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Sudoku Board");
+            self.draw_board(ui);
+            self.draw_number_pad(ui);
+            ui.horizontal(|ui| {
+                if ui.button("New game").clicked() {
+                    self.new_game();
+                }
+                if ui.button("Restart").clicked() {
+                    self.restart();
+                }
+                if ui.button("Undo").clicked() {
+                    self.undo();
+                }
+                if ui.button("Hint").clicked() {
+                    self.hint();
+                }
+                if let Some(cell_coordinate) = self.single_selected() {
+                    if ui
+                        .add_enabled(self.solution.is_some(), egui::Button::new("Reveal cell"))
+                        .clicked()
+                    {
+                        self.reveal_cell(cell_coordinate);
+                    }
+                }
+                if ui
+                    .button("Step")
+                    .on_hover_text("Point out a cell that can be filled by deduction")
+                    .clicked()
+                {
+                    self.show_next_step();
+                }
+                if ui.button("Check").clicked() {
+                    self.check();
+                }
+                if ui.button("Lock givens").clicked() {
+                    self.lock_givens();
+                }
+                if ui
+                    .add_enabled(
+                        self.solution.is_some() && !self.revealed,
+                        egui::Button::new("Reveal"),
+                    )
+                    .clicked()
+                {
+                    self.reveal_solution();
+                }
+                if ui.button("Save").clicked() {
+                    self.save_status = Some(match self.save_to_path(SAVE_FILE_PATH) {
+                        Ok(()) => "Game saved".to_string(),
+                        Err(save_error) => format!("Saving failed: {}", save_error),
                     });
                 }
+                if ui.button("Load").clicked() {
+                    match Self::load_from_path(SAVE_FILE_PATH) {
+                        Ok(loaded_app) => {
+                            self.replace_game(loaded_app);
+                            self.save_status = Some("Game loaded".to_string());
+                        }
+                        Err(save_error) => {
+                            self.save_status = Some(format!("Loading failed: {}", save_error))
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.puzzle_input)
+                        .hint_text("Paste an 81-character puzzle"),
+                );
+                if ui.button("Load puzzle").clicked() {
+                    let puzzle_input = self.puzzle_input.clone();
+                    if let Err(parse_error) = self.load_puzzle_str(&puzzle_input) {
+                        self.puzzle_input_error = Some(format!("Invalid puzzle: {}", parse_error));
+                    }
+                }
+                // Same 81-character format the paste box reads, empty cells as `.`
+                if ui.button("Copy board").clicked() {
+                    ui.ctx().copy_text(self.board.to_str_grid());
+                    self.save_status = Some("Board copied to the clipboard".to_string());
+                }
+            });
+            if let Some(puzzle_input_error) = &self.puzzle_input_error {
+                ui.colored_label(self.theme.conflict, puzzle_input_error);
+            }
+            if let Some(save_status) = &self.save_status {
+                ui.label(save_status);
+            }
+            if let Some(step) = &self.guided_step {
+                let (row, col) = step.cell_coordinate.to_rc();
+                ui.label(format!(
+                    "Row {}, column {}: look for a {}",
+                    row + 1,
+                    col + 1,
+                    step.technique
+                ));
+            }
+            ui.label(format!("Time: {}", format_duration(self.elapsed())));
+            match self.max_mistakes {
+                Some(max_mistakes) => {
+                    ui.label(format!("Mistakes: {}/{}", self.mistakes(), max_mistakes))
+                }
+                None => ui.label(format!("Mistakes: {}", self.mistakes())),
             };
-        }
+            if self.revealed {
+                ui.label("Solution revealed");
+            } else if self.is_game_over() {
+                ui.label("Game over");
+            } else if self.board.is_complete() {
+                ui.label(format!("Solved! Score: {}", self.score()));
+            } else if self.board.is_almost_complete() {
+                ui.label("One cell to go!");
+            }
+        });
+    }
+
+    // Digit buttons labelled with how many of the digit are left, placed digits are disabled
+    fn draw_number_pad(&mut self, ui: &mut egui::Ui) {
+        let remaining_counts = self.board.remaining_counts();
+        self.hovered_digit = None;
+        ui.horizontal(|ui| {
+            let mode_label = match self.input_mode {
+                InputMode::Pen => "Pen",
+                InputMode::Pencil => "Pencil",
+            };
+            let mode_button = ui
+                .button(mode_label)
+                .on_hover_text("Switch between placing digits and pencil marks (Space)");
+            if mode_button.clicked() {
+                self.input_mode = self.input_mode.toggled();
+                // A focused button would also take the next Space press and switch back
+                mode_button.surrender_focus();
+            }
+            for (value, remaining_count) in (1..=9).zip(remaining_counts) {
+                let button = egui::Button::new(format!("{}\n{}", value, remaining_count))
+                    .min_size(egui::Vec2::splat(CELL_SIZE * 0.8));
+                // Placed digits can still be pencil marked
+                let enabled = remaining_count > 0 || self.input_mode == InputMode::Pencil;
+                let response = ui.add_enabled(enabled, button);
+                if response.hovered() {
+                    self.hovered_digit = Some(value);
+                }
+                if response.clicked() {
+                    self.apply_digit(value, self.input_mode);
+                }
+            }
+        });
+    }
 
-        if let Some(row_duplicates) = self.get_row_duplicates(sudoku_move) {
-            invalid_cells_coordinates.extend(row_duplicates);
+    fn draw_board(&mut self, ui: &mut egui::Ui) {
+        let (grid_rect, response) =
+            ui.allocate_exact_size(egui::Vec2::splat(GRID_SIZE), egui::Sense::click_and_drag());
+        // Shift-click adds or removes a cell, dragging sweeps cells into the selection
+        let shift_held = ui.input(|input| input.modifiers.shift);
+        let pointer_cell = response
+            .interact_pointer_pos()
+            .and_then(|pointer_pos| cell_at(grid_rect, pointer_pos));
+        if let Some(pointer_cell) = pointer_cell {
+            if response.clicked() {
+                if !shift_held {
+                    self.selected.clear();
+                    self.selected.insert(pointer_cell);
+                } else if !self.selected.remove(&pointer_cell) {
+                    self.selected.insert(pointer_cell);
+                }
+            } else if response.dragged() {
+                if response.drag_started() && !shift_held {
+                    self.selected.clear();
+                }
+                self.selected.insert(pointer_cell);
+            }
         }
 
-        if let Some(col_duplicates) = self.get_column_duplicates(sudoku_move) {
-            invalid_cells_coordinates.extend(col_duplicates);
+        let painter = ui.painter();
+        let theme = self.theme;
+        painter.rect_filled(grid_rect, 0.0, theme.background);
+        // The number pad is drawn after the board, so this is the digit hovered last frame
+        let placements = self
+            .hovered_digit
+            .map(|value| self.board.placements_for(value))
+            .unwrap_or_default();
+        let same_value_cells = self
+            .single_selected()
+            .and_then(|selected| self.board.get_value(selected))
+            .map(|value| self.board.cells_with_value(value))
+            .unwrap_or_default();
+        let peers = self
+            .single_selected()
+            .map(|selected| self.board.peers(&selected))
+            .unwrap_or_default();
+        let flashing_cells = match &self.completed_flash {
+            Some((cells, until)) if Instant::now() < *until => cells.as_slice(),
+            _ => &[],
+        };
+
+        for (cell_coordinate, value) in self.board.iter_cells() {
+            let (row, col) = cell_coordinate.to_rc();
+            if self.conflicts.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.conflict);
+            } else if self.wrong_entries.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.wrong_entry);
+            } else if self.selected.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.selected);
+            } else if same_value_cells.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.same_value);
+            } else if flashing_cells.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.completed);
+            } else if placements.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.placement);
+            } else if peers.contains(&cell_coordinate) {
+                painter.rect_filled(cell_rect(grid_rect, row, col), 0.0, theme.peer);
+            }
+            if let Some(value) = value {
+                let (font_family, color) = if self.board.is_given(cell_coordinate) {
+                    (egui::FontFamily::Name(GIVEN_FONT.into()), theme.given)
+                } else if self.revealed_cells.contains(&cell_coordinate) {
+                    (egui::FontFamily::Proportional, theme.revealed_entry)
+                } else {
+                    (egui::FontFamily::Proportional, theme.user_entry)
+                };
+                painter.text(
+                    cell_rect(grid_rect, row, col).center(),
+                    egui::Align2::CENTER_CENTER,
+                    value.to_string(),
+                    egui::FontId::new(CELL_SIZE * 0.6, font_family),
+                    color,
+                );
+            } else {
+                draw_candidates(
+                    painter,
+                    cell_rect(grid_rect, row, col),
+                    &self.board.get_candidates(cell_coordinate),
+                    theme.candidate,
+                );
+            }
         }
 
-        if invalid_cells_coordinates.len() > 0 {
-            SudokuMoveResult::Invalid(invalid_cells_coordinates)
-        } else {
-            SudokuMoveResult::Ok
+        // Sub-grid borders are drawn thicker than the lines between cells
+        for line in 0..=9 {
+            let width = if line % 3 == 0 {
+                SUB_GRID_LINE_WIDTH
+            } else {
+                CELL_LINE_WIDTH
+            };
+            let stroke = egui::Stroke::new(width, theme.grid_line);
+            let offset = line as f32 * CELL_SIZE;
+            painter.vline(grid_rect.left() + offset, grid_rect.y_range(), stroke);
+            painter.hline(grid_rect.x_range(), grid_rect.top() + offset, stroke);
         }
     }
 }
 
-struct SudokuApp {
-    board: SudokuBoard,
-    move_history: Vec<SudokuMove>,
-    nr_mistakes: u8,
+/// Formats a duration as MM:SS
+fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    format!("{:02}:{:02}", total_seconds / 60, total_seconds % 60)
 }
 
-impl SudokuApp {
-    fn new() -> Self {
-        Self {
-            board: SudokuBoard::new(),
-            move_history: Vec::new(),
-            nr_mistakes: 0_u8,
-        }
+fn cell_rect(grid_rect: egui::Rect, row: usize, col: usize) -> egui::Rect {
+    egui::Rect::from_min_size(
+        grid_rect.min + egui::vec2(col as f32 * CELL_SIZE, row as f32 * CELL_SIZE),
+        egui::Vec2::splat(CELL_SIZE),
+    )
+}
+
+// Lays the candidates out as a 3x3 grid of small digits inside the cell
+fn draw_candidates(
+    painter: &egui::Painter,
+    cell_rect: egui::Rect,
+    candidates: &HashSet<u8>,
+    color: egui::Color32,
+) {
+    let mark_size = CELL_SIZE / 3.0;
+    for value in candidates {
+        let index = (*value - 1) as f32;
+        let mark_center = cell_rect.min
+            + egui::vec2(
+                (index % 3.0 + 0.5) * mark_size,
+                ((index / 3.0).floor() + 0.5) * mark_size,
+            );
+        painter.text(
+            mark_center,
+            egui::Align2::CENTER_CENTER,
+            value.to_string(),
+            egui::FontId::proportional(mark_size * 0.8),
+            color,
+        );
     }
+}
 
-    fn update_grid(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Sudoku Board");
-        });
+fn cell_at(grid_rect: egui::Rect, pos: egui::Pos2) -> Option<CellCoordinate> {
+    if !grid_rect.contains(pos) {
+        return None;
     }
+    let offset = (pos - grid_rect.min) / CELL_SIZE;
+    CellCoordinate::from_rc(offset.y as usize, offset.x as usize)
+}
+
+/// Moves the selection one cell in the direction, staying on the edge instead of wrapping
+fn step_selection(cell_coordinate: CellCoordinate, direction: Direction) -> CellCoordinate {
+    let (row, col) = cell_coordinate.to_rc();
+    let (row, col) = match direction {
+        Direction::Up => (row.saturating_sub(1), col),
+        Direction::Down => ((row + 1).min(8), col),
+        Direction::Left => (row, col.saturating_sub(1)),
+        Direction::Right => (row, (col + 1).min(8)),
+    };
+    CellCoordinate::from_rc(row, col).unwrap_or(cell_coordinate)
 }
 
 impl eframe::App for SudokuApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_keys(ctx);
+        self.update_menu(ctx);
+        self.update_stats(ctx);
         self.update_grid(ctx);
+        if self.finished_in.is_none() {
+            // Keeps the clock ticking without user input
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        if let Some((_, until)) = &self.completed_flash {
+            // Repaints once more to take the highlight away
+            ctx.request_repaint_after(until.saturating_duration_since(Instant::now()));
+        }
     }
 }
 
+fn install_fonts(ctx: &egui::Context) {
+    let mut fonts = egui::FontDefinitions::default();
+    fonts.font_data.insert(
+        GIVEN_FONT.to_owned(),
+        std::sync::Arc::new(egui::FontData::from_static(include_bytes!(
+            "../assets/fonts/FiraSans-Bold.ttf"
+        ))),
+    );
+    fonts.families.insert(
+        egui::FontFamily::Name(GIVEN_FONT.into()),
+        vec![GIVEN_FONT.to_owned()],
+    );
+    ctx.set_fonts(fonts);
+}
+
+/// Reads an 81-character puzzle from stdin and returns its solution in the same format
+fn solve_stdin() -> Result<String, String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|error| format!("failed to read stdin: {}", error))?;
+    let mut board = SudokuBoard::from_str_grid(input.trim())
+        .map_err(|parse_error| format!("malformed puzzle: {}", parse_error))?;
+    if !board.solve_with_heuristic() {
+        return Err("puzzle has no solution".to_string());
+    }
+    Ok(board.to_str_grid())
+}
+
 fn main() -> Result<(), eframe::Error> {
-    let options = eframe::NativeOptions::default();
+    // Move logging is off unless asked for, e.g. with RUST_LOG=sudoku_rs=debug
+    env_logger::init();
+    // Headless mode for scripts, the window is never opened
+    if env::args().any(|arg| arg == "--solve") {
+        match solve_stdin() {
+            Ok(solution) => {
+                println!("{}", solution);
+                return Ok(());
+            }
+            Err(message) => {
+                eprintln!("{}", message);
+                process::exit(1);
+            }
+        }
+    }
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([
+            GRID_SIZE + WINDOW_MARGIN + STATS_PANEL_WIDTH,
+            GRID_SIZE + 2.0 * WINDOW_MARGIN,
+        ]),
+        ..Default::default()
+    };
     eframe::run_native(
         "Sudoku Grid",
         options,
-        Box::new(|_cc| Ok(Box::new(SudokuApp::new()))),
+        Box::new(|cc| {
+            install_fonts(&cc.egui_ctx);
+            let app = SudokuApp::new();
+            cc.egui_ctx.set_visuals(app.theme.visuals());
+            Ok(Box::new(app))
+        }),
     )
 }
- 
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
     use super::*; // Import functions from the parent module
+    use crate::test_fixtures::*;
 
     #[test]
-    fn keys_with_duplicate_values_empty_input() {
-        let mut empty_test_cells: HashMap<PositionId, Option<u8>> = HashMap::new();
-        let vec = keys_with_duplicate_values(&empty_test_cells);
-        assert_eq!(vec.len() , 0);
+    fn undo_reverts_only_last_move() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 0),
+            value: 1,
+        });
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(4, 4),
+            value: 2,
+        });
+
+        app.undo();
+        assert_eq!(Some(1), app.board.get_value(coordinate(0, 0)));
+        assert_eq!(None, app.board.get_value(coordinate(4, 4)));
+        assert_eq!(1, app.move_history.len());
+        assert_eq!(0, app.nr_mistakes);
     }
-    
+
     #[test]
-    fn keys_with_duplicate_values_no_duplicates_input() {
-        let mut no_duplicate_test_cells: HashMap<PositionId, Option<u8>> = HashMap::new();
-        no_duplicate_test_cells.insert(
-            PositionId { 
-                row: Row::Upper, 
-                column: Column::Left
-            }, 
-            Some(1)
+    fn undo_restores_overwritten_value() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        for value in [3, 7] {
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(2, 6),
+                value,
+            });
+        }
+
+        app.undo();
+        assert_eq!(Some(3), app.board.get_value(coordinate(2, 6)));
+    }
+
+    #[test]
+    fn digit_without_selected_cell_is_ignored() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.enter_digit(5);
+        assert!(app.move_history.is_empty());
+        assert_eq!([[0_u8; 9]; 9], app.board.to_array());
+    }
+
+    #[test]
+    fn digit_is_entered_into_selected_cell_and_erased() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.selected = HashSet::from([coordinate(6, 2)]);
+        app.enter_digit(5);
+        assert_eq!(Some(5), app.board.get_value(coordinate(6, 2)));
+
+        app.erase_selected();
+        assert_eq!(None, app.board.get_value(coordinate(6, 2)));
+    }
+
+    #[test]
+    fn cell_at_maps_pointer_to_coordinate() {
+        let grid_rect =
+            egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::Vec2::splat(GRID_SIZE));
+        let pointer_pos = cell_rect(grid_rect, 7, 3).center();
+        assert_eq!(Some(coordinate(7, 3)), cell_at(grid_rect, pointer_pos));
+        assert_eq!(None, cell_at(grid_rect, egui::pos2(0.0, 0.0)));
+    }
+
+    #[test]
+    fn conflicts_lose_highlight_once_resolved() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.selected = HashSet::from([coordinate(0, 0)]);
+        app.enter_digit(5);
+        app.selected = HashSet::from([coordinate(0, 8)]);
+        app.enter_digit(5);
+        assert!(app.conflicts.contains(&coordinate(0, 0)));
+        assert!(app.conflicts.contains(&coordinate(0, 8)));
+
+        app.erase_selected();
+        assert!(app.conflicts.is_empty());
+
+        app.enter_digit(5);
+        assert!(!app.conflicts.is_empty());
+        app.undo();
+        assert!(app.conflicts.is_empty());
+    }
+
+    #[test]
+    fn three_invalid_moves_end_the_game() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 8),
+            value: 5,
+        });
+        for col in 0..3 {
+            assert!(!app.is_game_over());
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, col),
+                value: 5,
+            });
+        }
+        assert_eq!(3, app.mistakes());
+        assert!(app.is_game_over());
+    }
+
+    #[test]
+    fn valid_moves_do_not_count_as_mistakes() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        for (col, value) in (0..9).zip(1..=9) {
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, col),
+                value,
+            });
+        }
+        assert_eq!(0, app.mistakes());
+        assert!(!app.is_game_over());
+    }
+
+    #[test]
+    fn no_mistake_limit_never_ends_the_game() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.max_mistakes = None;
+        for col in 0..5 {
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, col),
+                value: 5,
+            });
+        }
+        assert!(!app.is_game_over());
+    }
+
+    #[test]
+    fn hint_fills_correct_digit() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        let hinted_cell = app.hint().expect("Puzzle has a unique solution");
+        let (row, col) = hinted_cell.to_rc();
+        assert_eq!(0, PUZZLE_GRID[row][col]);
+        assert_eq!(
+            Some(SOLVED_GRID[row][col]),
+            app.board.get_value(hinted_cell)
         );
-        no_duplicate_test_cells.insert(
-            PositionId { 
-                row: Row::Upper, 
-                column: Column::Center
-            }, 
-            Some(2)
+        assert_eq!(1, app.hints_used);
+        assert_eq!(1, app.move_history.len());
+    }
+
+    #[test]
+    fn hint_after_wrong_entry_uses_the_puzzle_solution() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        // Breaks no rule but isn't the solution's 4
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 2,
+        });
+        assert_eq!(Some(2), app.board.get_value(coordinate(0, 2)));
+        assert_eq!(None, app.board.solution());
+
+        let hinted_cell = app.hint().expect("Puzzle has a unique solution");
+        assert_eq!(coordinate(0, 3), hinted_cell);
+        assert_eq!(Some(SOLVED_GRID[0][3]), app.board.get_value(hinted_cell));
+    }
+
+    #[test]
+    fn hint_next_to_wrong_entry_with_its_digit_is_not_a_mistake() {
+        for reject_conflicting_input in [true, false] {
+            let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+            app.reject_conflicting_input = reject_conflicting_input;
+            // Breaks no rule but the solution has 8 there, and 6 at (0, 3)
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, 5),
+                value: 6,
+            });
+            let nr_mistakes = app.mistakes();
+            app.selected = HashSet::from([coordinate(0, 3)]);
+
+            assert_eq!(Some(coordinate(0, 3)), app.hint());
+            assert_eq!(
+                Some(SOLVED_GRID[0][3]),
+                app.board.get_value(coordinate(0, 3))
+            );
+            assert_eq!(nr_mistakes, app.mistakes());
+            assert_eq!(1, app.hints_used);
+            assert_eq!(2, app.move_history.len());
+        }
+    }
+
+    #[test]
+    fn hint_does_nothing_once_the_game_is_over() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        app.nr_mistakes = DEFAULT_MAX_MISTAKES;
+        assert_eq!(None, app.hint());
+        assert_eq!(0, app.hints_used);
+        assert_eq!(PUZZLE_GRID, app.board.to_array());
+    }
+
+    #[test]
+    fn hint_on_complete_board_does_nothing() {
+        let mut app = SudokuApp::with_board(board_from_rows(SOLVED_GRID));
+        assert_eq!(None, app.hint());
+        assert_eq!(0, app.hints_used);
+    }
+
+    #[test]
+    fn next_step_selects_the_cell_without_filling_it() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        let step = app.show_next_step().expect("Puzzle has a naked single");
+        assert_eq!(HashSet::from([step.cell_coordinate]), app.selected);
+        assert_eq!(None, app.board.get_value(step.cell_coordinate));
+        assert_eq!(0, app.hints_used);
+
+        app.apply_digit(step.value, InputMode::Pen);
+        assert_eq!(None, app.guided_step);
+    }
+
+    #[test]
+    fn format_duration_as_minutes_and_seconds() {
+        assert_eq!("00:00", format_duration(Duration::ZERO));
+        assert_eq!("01:05", format_duration(Duration::from_secs(65)));
+        assert_eq!("59:59", format_duration(Duration::from_millis(3_599_900)));
+    }
+
+    #[test]
+    fn timer_stops_once_solved_and_resets() {
+        let mut almost_solved_grid = SOLVED_GRID;
+        almost_solved_grid[8][8] = 0;
+        let mut app = SudokuApp::with_board(board_from_rows(almost_solved_grid));
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(8, 8),
+            value: SOLVED_GRID[8][8],
+        });
+        let elapsed_at_completion = app.elapsed();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(elapsed_at_completion, app.elapsed());
+
+        app.reset_timer();
+        assert!(app.finished_in.is_none());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut app = SudokuApp::with_board(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
         );
-        no_duplicate_test_cells.insert(
-            PositionId { 
-                row: Row::Upper, 
-                column: Column::Right
-            }, 
-            Some(3)
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 4,
+        });
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 3),
+            value: 5,
+        });
+        let _ = app.board.toggle_candidate(coordinate(8, 0), 3);
+
+        let save_path =
+            std::env::temp_dir().join(format!("sudoku_rs_save_{}.json", std::process::id()));
+        app.save_to_path(&save_path).expect("Temp dir is writable");
+        let loaded_app = SudokuApp::load_from_path(&save_path).expect("Save file was just written");
+        let _ = fs::remove_file(&save_path);
+
+        assert!(app.board == loaded_app.board);
+        assert!(loaded_app.board.is_given(coordinate(0, 0)));
+        assert_eq!(
+            HashSet::from([3]),
+            loaded_app.board.get_candidates(coordinate(8, 0))
         );
-        let vec = keys_with_duplicate_values(&no_duplicate_test_cells);
-        assert_eq!(vec.len() , 0);
+        assert_eq!(2, loaded_app.move_history.len());
+        for (entry, loaded_entry) in app.move_history.iter().zip(&loaded_app.move_history) {
+            assert_eq!(entry.elapsed, loaded_entry.elapsed);
+        }
+        assert_eq!(1, loaded_app.mistakes());
+        assert!(loaded_app.elapsed() >= app.elapsed().saturating_sub(Duration::from_secs(1)));
     }
-    
+
     #[test]
-    fn keys_with_duplicate_values_detects_duplicates() {
-        let mut no_duplicate_test_cells: HashMap<PositionId, Option<u8>> = HashMap::new();
+    fn new_game_resets_history_mistakes_and_timer() {
+        let mut app = SudokuApp::with_board(board_from_rows(SOLVED_GRID));
+        let _ = app.board.clear_value(coordinate(0, 0));
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 0),
+            value: 3,
+        });
+        app.completed_flash = Some((vec![coordinate(0, 0)], Instant::now()));
+        app.puzzle_input_error = Some("Invalid puzzle".to_string());
+        app.new_game();
+        assert!(app.board == SudokuBoard::new());
+        assert!(app.move_history.is_empty());
+        assert_eq!(0, app.mistakes());
+        assert!(app.finished_in.is_none());
+        assert!(app.completed_flash.is_none());
+        assert!(app.puzzle_input_error.is_none());
+    }
 
-        let position_match_1 = PositionId { 
-            row: Row::Upper, 
-            column: Column::Left
-        };
+    #[test]
+    fn arrows_step_selection_and_clamp_at_edges() {
+        assert_eq!(
+            coordinate(3, 4),
+            step_selection(coordinate(4, 4), Direction::Up)
+        );
+        assert_eq!(
+            coordinate(5, 4),
+            step_selection(coordinate(4, 4), Direction::Down)
+        );
+        assert_eq!(
+            coordinate(4, 3),
+            step_selection(coordinate(4, 4), Direction::Left)
+        );
+        assert_eq!(
+            coordinate(2, 3),
+            step_selection(coordinate(2, 2), Direction::Right)
+        );
+        assert_eq!(
+            coordinate(0, 0),
+            step_selection(coordinate(0, 0), Direction::Up)
+        );
+        assert_eq!(
+            coordinate(0, 0),
+            step_selection(coordinate(0, 0), Direction::Left)
+        );
+        assert_eq!(
+            coordinate(8, 8),
+            step_selection(coordinate(8, 8), Direction::Down)
+        );
+        assert_eq!(
+            coordinate(8, 8),
+            step_selection(coordinate(8, 8), Direction::Right)
+        );
+    }
 
-        let position_match_2 = PositionId { 
-            row: Row::Upper, 
-            column: Column::Center
-        }; 
-        no_duplicate_test_cells.insert(
-            position_match_1
-            , 
-            Some(1)
+    #[test]
+    fn check_reports_legal_but_wrong_entry() {
+        let mut app = SudokuApp::with_board(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
         );
-        no_duplicate_test_cells.insert(
-        position_match_2,
-            Some(1)
+        // 2 breaks no rule of row 0, column 2 or the top left sub-grid, but the solution has 4 there
+        assert!(matches!(
+            app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 2
+            }),
+            SudokuMoveResult::Ok { .. }
+        ));
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 3),
+            value: 6,
+        });
+        assert_eq!(vec![coordinate(0, 2)], app.check());
+
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 4,
+        });
+        assert!(app.check().is_empty());
+    }
+
+    #[test]
+    fn theme_presets_differ_in_background() {
+        assert_ne!(Theme::light().background, Theme::dark().background);
+        assert!(!Theme::light().dark);
+        assert!(Theme::dark().dark);
+    }
+
+    #[test]
+    fn candidates_toggle_across_selection_and_digits_need_one_cell() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.selected = HashSet::from([coordinate(2, 2), coordinate(4, 7)]);
+        app.toggle_selected_candidates(6);
+        assert_eq!(
+            HashSet::from([6]),
+            app.board.get_candidates(coordinate(2, 2))
+        );
+        assert_eq!(
+            HashSet::from([6]),
+            app.board.get_candidates(coordinate(4, 7))
+        );
+
+        app.enter_digit(6);
+        assert!(app.move_history.is_empty());
+        assert_eq!([[0_u8; 9]; 9], app.board.to_array());
+    }
+
+    #[test]
+    fn undoing_invalid_move_takes_back_the_mistake() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 5,
+        });
+        assert_eq!(1, app.mistakes());
+
+        app.undo();
+        assert_eq!(0, app.mistakes());
+        assert!(app.move_history.is_empty());
+    }
+
+    #[test]
+    fn score_decreases_with_every_mistake() {
+        let mut app = SudokuApp::with_board(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
         );
-        no_duplicate_test_cells.insert(
-            PositionId { 
-                row: Row::Upper, 
-                column: Column::Right
-            }, 
-            Some(3)
+        app.finished_in = Some(Duration::from_secs(120));
+        let scores: Vec<u32> = (0..5)
+            .map(|nr_mistakes| {
+                app.nr_mistakes = nr_mistakes;
+                app.score()
+            })
+            .collect();
+        // Naked singles solve the puzzle, so it rates Easy
+        assert_eq!(1000 - 120, scores[0]);
+        assert!(
+            scores.windows(2).all(|pair| pair[1] < pair[0]),
+            "{:?}",
+            scores
         );
-        no_duplicate_test_cells.insert(
-            PositionId { 
-                row: Row::Upper, 
-                column: Column::Right
-            }, 
-            Some(4)
+
+        app.nr_mistakes = 0;
+        app.hints_used = 1;
+        assert_eq!(1000 - 120 - 200, app.score());
+        app.revealed = true;
+        assert_eq!(0, app.score());
+    }
+
+    #[test]
+    fn reveal_cell_fills_only_that_cell() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        app.reveal_cell(coordinate(4, 4));
+        let mut expected_rows = PUZZLE_GRID;
+        expected_rows[4][4] = SOLVED_GRID[4][4];
+        assert_eq!(expected_rows, app.board.to_array());
+        assert_eq!(HashSet::from([coordinate(4, 4)]), app.revealed_cells);
+        assert_eq!(1, app.hints_used);
+
+        // Already correct: a given, and the cell just revealed
+        app.reveal_cell(coordinate(0, 0));
+        app.reveal_cell(coordinate(4, 4));
+        assert_eq!(expected_rows, app.board.to_array());
+        assert_eq!(1, app.hints_used);
+
+        let mut ambiguous_app = SudokuApp::with_board(SudokuBoard::new());
+        ambiguous_app.reveal_cell(coordinate(4, 4));
+        assert_eq!(None, ambiguous_app.board.get_value(coordinate(4, 4)));
+    }
+
+    #[test]
+    fn reveal_fills_solution_and_stops_input() {
+        let mut app = SudokuApp::with_board(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
         );
-        let matched_keys = keys_with_duplicate_values(&no_duplicate_test_cells);
-        assert_eq!(matched_keys.len() , 2);
-        let mut was_match_1_returned = false;
-        let mut was_match_2_returned = false;
-        for matched_key in matched_keys {
-            if matched_key == position_match_1 {
-                was_match_1_returned = true;
-            }
-            if matched_key == position_match_2 {
-                was_match_2_returned = true;
-            } 
-        }
-        assert_eq!(true, was_match_1_returned & was_match_2_returned);
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 1,
+        });
+        app.reveal_solution();
+        assert_eq!(SOLVED_GRID, app.board.to_array());
+        assert!(app.board.is_complete());
+        assert!(app.revealed);
+        assert!(app.finished_in.is_some());
 
+        app.selected = HashSet::from([coordinate(0, 2)]);
+        app.erase_selected();
+        app.undo();
+        assert_eq!(Some(4), app.board.get_value(coordinate(0, 2)));
     }
 
     #[test]
-    fn new_subgrid_returns_all_empty_cells() {
-        let empty_subgrid = SubGrid::new();
-        for cell in empty_subgrid.cells.iter() {
-            assert_eq!(None, *cell.1);
-        }
+    fn reveal_without_solution_does_nothing() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.reveal_solution();
+        assert!(!app.revealed);
+        assert_eq!(81, app.board.empty_cells().len());
     }
 
     #[test]
-    fn update_cell_value_in_subgrid() {
-        let mut mut_subgrid = SubGrid::new();
-        let arbitrary_position = PositionId {  
-            row: Row::Center,
-            column: Column::Right
-        };
-        let arbitrary_value = 8_u8;
-        let _ = mut_subgrid.update_value(arbitrary_position, arbitrary_value);
-        assert_eq!(arbitrary_value, mut_subgrid.cells[&arbitrary_position].expect("Value just updated, shouldn't be None") );
+    fn completing_a_row_flashes_its_cells() {
+        let mut board = board_from_rows(SOLVED_GRID);
+        let _ = board.clear_value(coordinate(0, 0));
+        let _ = board.clear_value(coordinate(1, 1));
+        let mut app = SudokuApp::with_board(board);
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(1, 1),
+            value: 7,
+        });
+        let (flashing_cells, _) = app.completed_flash.clone().expect("Row 1 was completed");
+        assert!(flashing_cells.contains(&coordinate(1, 8)));
+        assert!(flashing_cells.contains(&coordinate(8, 1)));
+        assert!(!flashing_cells.contains(&coordinate(0, 8)));
     }
 
     #[test]
-    fn update_cell_in_subgrid_with_invalid_value_fails() {
-        let mut mut_subgrid = SubGrid::new();
-        let arbitrary_position = PositionId {  
-            row: Row::Center,
-            column: Column::Right
-        };
-        let arbitrary_invalid_value = 10_u8;
-        let ret_err = mut_subgrid.update_value(arbitrary_position, arbitrary_invalid_value);
-        assert!(matches!(ret_err,Err(_)));
-        assert_eq!(None, mut_subgrid.cells[&arbitrary_position]);
-    }
-    
-    #[test]
-    fn get_value_from_subgrid() {
-        let mut mut_subgrid = SubGrid::new();
-        let arbitrary_position = PositionId {  
-            row: Row::Center,
-            column: Column::Left
-        };
-        let arbitrary_empty_cell = PositionId {  
-            row: Row::Bottom,
-            column: Column::Center
-        };
-        let arbitrary_value = 6_u8;
-        let _ = mut_subgrid.update_value(arbitrary_position, arbitrary_value);
-        assert_eq!(arbitrary_value, mut_subgrid.get_value(arbitrary_position).expect("Value just updated, shouldn't be None"));
-        assert_eq!(None, mut_subgrid.get_value(arbitrary_empty_cell));
+    fn pasted_puzzle_loads_with_whitespace_stripped() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        let pasted_puzzle: String = PUZZLE_STR
+            .as_bytes()
+            .chunks(9)
+            .map(|row| format!(" {}\r\n", String::from_utf8_lossy(row)))
+            .collect();
+        assert_eq!(Ok(()), app.load_puzzle_str(&pasted_puzzle));
+        assert_eq!(PUZZLE_STR, app.board.to_str_grid());
+        assert!(app.board.is_given(coordinate(0, 0)));
+        assert!(app.solution.is_some());
+
+        assert_eq!(
+            Err(LoadPuzzleError::Parse(ParseError::WrongLength {
+                expected: 81,
+                found: 80
+            })),
+            app.load_puzzle_str(&PUZZLE_STR[1..])
+        );
+        assert_eq!(
+            Err(LoadPuzzleError::Puzzle(PuzzleError::NotUnique)),
+            app.load_puzzle_str(&".".repeat(81))
+        );
+        assert_eq!(PUZZLE_STR, app.board.to_str_grid());
     }
 
     #[test]
-    fn make_no_duplicate_move_in_subgrid() {
-        let mut mut_subgrid = SubGrid::new();
-        let arbitrary_position = PositionId {  
-            row: Row::Center,
-            column: Column::Left
-        };
-        let arbitrary_value = 6_u8;
-        let sub_grid_move = SubGridMove { 
-            cell: arbitrary_position,
-            value: arbitrary_value
-        };
+    fn locked_entries_become_givens_of_a_fresh_game() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 4,
+        });
+        app.lock_givens();
+        assert!(app.board.is_given(coordinate(0, 2)));
+        assert!(app.move_history.is_empty());
+        assert!(app.solution.is_some());
 
-        let move_result = mut_subgrid.make_move(sub_grid_move);
-        assert_eq!(SubgridMoveResult::Ok, move_result);
-        assert_eq!(arbitrary_value, mut_subgrid.get_value(arbitrary_position).expect("Value just updated, shouldn't be None"));
+        app.undo();
+        assert_eq!(Some(4), app.board.get_value(coordinate(0, 2)));
     }
 
     #[test]
-    fn make_duplicate_move_in_subgrid() {
-        let mut mut_subgrid = SubGrid::new();
-        let arbitrary_position = PositionId {  
-            row: Row::Center,
-            column: Column::Left
-        };
-        let arbitrary_value = 6_u8;
-        let sub_grid_move_1 = SubGridMove { 
-            cell: arbitrary_position,
-            value: arbitrary_value
-        };
+    fn auto_prune_only_runs_when_turned_on() {
+        for auto_prune_candidates in [false, true] {
+            let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+            app.auto_prune_candidates = auto_prune_candidates;
+            let _ = app.board.toggle_candidate(coordinate(0, 3), 4);
+            let _ = app.make_move(SudokuMove {
+                cell_coordinate: coordinate(0, 2),
+                value: 4,
+            });
+            assert_eq!(
+                !auto_prune_candidates,
+                app.board.get_candidates(coordinate(0, 3)).contains(&4)
+            );
+        }
+    }
 
-        let arbitrary_position_2 = PositionId {  
-            row: Row::Center,
-            column: Column::Right
-        };
-        let sub_grid_move_2 = SubGridMove { 
-            cell: arbitrary_position_2,
-            value: arbitrary_value
-        };
+    #[test]
+    fn conflicting_move_is_only_placed_when_not_rejected() {
+        for reject_conflicting_input in [true, false] {
+            let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+            app.reject_conflicting_input = reject_conflicting_input;
+            app.selected = HashSet::from([coordinate(0, 2)]);
+            // 5 is already in row 0 and the top left sub-grid
+            app.apply_digit(5, InputMode::Pen);
+            let expected_value = if reject_conflicting_input {
+                None
+            } else {
+                Some(5)
+            };
+            assert_eq!(expected_value, app.board.get_value(coordinate(0, 2)));
+            assert_eq!(vec![coordinate(0, 0), coordinate(0, 2)], app.conflicts);
+            assert_eq!(1, app.mistakes());
 
-        let _ = mut_subgrid.make_move(sub_grid_move_1);
-        let invalid_move = mut_subgrid.make_move(sub_grid_move_2);
-        
-        // Use pattern matching to extract the vector and compare
-        if let SubgridMoveResult::Invalid(positions) = invalid_move {
-            let expected_positions: HashSet<_> = vec![arbitrary_position,arbitrary_position_2 ].into_iter().collect();
-            let actual_positions: HashSet<_> = positions.into_iter().collect();
-            assert_eq!(expected_positions, actual_positions);
-        } else {
-            panic!("Expected SubgridMoveResult::Invalid, got {:?}", invalid_move);
+            app.undo();
+            assert_eq!(None, app.board.get_value(coordinate(0, 2)));
+            assert_eq!(0, app.mistakes());
         }
+    }
 
+    #[test]
+    fn digits_are_placed_in_pen_mode_and_marked_in_pencil_mode() {
+        let mut app = SudokuApp::with_board(SudokuBoard::new());
+        app.selected = HashSet::from([coordinate(3, 3)]);
+        app.apply_digit(8, InputMode::Pencil);
+        assert_eq!(None, app.board.get_value(coordinate(3, 3)));
+        assert_eq!(
+            HashSet::from([8]),
+            app.board.get_candidates(coordinate(3, 3))
+        );
+
+        app.apply_digit(8, InputMode::Pen);
+        assert_eq!(Some(8), app.board.get_value(coordinate(3, 3)));
+        assert_eq!(InputMode::Pencil, InputMode::Pen.toggled());
+        assert_eq!(InputMode::Pen, InputMode::Pencil.toggled());
+    }
+
+    #[test]
+    fn tab_selection_moves_to_next_empty_cell() {
+        let mut app = SudokuApp::with_board(board_from_rows(PUZZLE_GRID));
+        app.select_next_empty();
+        assert_eq!(HashSet::from([coordinate(0, 2)]), app.selected);
+        app.select_next_empty();
+        assert_eq!(HashSet::from([coordinate(0, 3)]), app.selected);
+        app.selected = HashSet::from([coordinate(8, 7)]);
+        app.select_next_empty();
+        assert_eq!(HashSet::from([coordinate(0, 2)]), app.selected);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn restart_keeps_only_givens_and_resets_mistakes() {
+        let mut app = SudokuApp::with_board(
+            SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed"),
+        );
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 2),
+            value: 4,
+        });
+        let _ = app.make_move(SudokuMove {
+            cell_coordinate: coordinate(0, 3),
+            value: 5,
+        });
+        let _ = app.board.toggle_candidate(coordinate(8, 0), 3);
+        assert_eq!(1, app.mistakes());
+
+        app.restart();
+        assert_eq!(PUZZLE_STR, app.board.to_str_grid());
+        assert!(app.board.get_candidates(coordinate(8, 0)).is_empty());
+        assert!(app.board.is_given(coordinate(0, 0)));
+        assert_eq!(0, app.mistakes());
+        assert!(app.move_history.is_empty());
+        assert!(app.solution.is_some());
+    }
+}
@@ -0,0 +1,40 @@
+//! Puzzle shared by the unit tests of the library and of the app, both declare this module
+
+use crate::{CellCoordinate, SudokuBoard};
+
+pub const SOLVED_GRID: [[u8; 9]; 9] = [
+    [5, 3, 4, 6, 7, 8, 9, 1, 2],
+    [6, 7, 2, 1, 9, 5, 3, 4, 8],
+    [1, 9, 8, 3, 4, 2, 5, 6, 7],
+    [8, 5, 9, 7, 6, 1, 4, 2, 3],
+    [4, 2, 6, 8, 5, 3, 7, 9, 1],
+    [7, 1, 3, 9, 2, 4, 8, 5, 6],
+    [9, 6, 1, 5, 3, 7, 2, 8, 4],
+    [2, 8, 7, 4, 1, 9, 6, 3, 5],
+    [3, 4, 5, 2, 8, 6, 1, 7, 9],
+];
+
+// Zeros are empty cells
+pub const PUZZLE_GRID: [[u8; 9]; 9] = [
+    [5, 3, 0, 0, 7, 0, 0, 0, 0],
+    [6, 0, 0, 1, 9, 5, 0, 0, 0],
+    [0, 9, 8, 0, 0, 0, 0, 6, 0],
+    [8, 0, 0, 0, 6, 0, 0, 0, 3],
+    [4, 0, 0, 8, 0, 3, 0, 0, 1],
+    [7, 0, 0, 0, 2, 0, 0, 0, 6],
+    [0, 6, 0, 0, 0, 0, 2, 8, 0],
+    [0, 0, 0, 4, 1, 9, 0, 0, 5],
+    [0, 0, 0, 0, 8, 0, 0, 7, 9],
+];
+
+pub const PUZZLE_STR: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+// Maps a 0-8 row/column pair onto the nested sub-grid/cell coordinate
+pub fn coordinate(row: usize, col: usize) -> CellCoordinate {
+    CellCoordinate::from_rc(row, col).expect("Test coordinates are in range")
+}
+
+pub fn board_from_rows(rows: [[u8; 9]; 9]) -> SudokuBoard {
+    SudokuBoard::from_array(rows).expect("Test grids only hold values up to 9")
+}
@@ -0,0 +1,4 @@
+//! Fixtures shared by the integration tests, each test file declares `mod common;`
+
+pub const PUZZLE_STR: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
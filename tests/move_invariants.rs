@@ -0,0 +1,54 @@
+mod common;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sudoku_rs::prelude::*;
+
+use common::PUZZLE_STR;
+
+const NR_MOVES: usize = 2000;
+
+// Random moves on an empty board and on a puzzle with givens, occasionally clearing a cell so the
+// board keeps taking moves instead of filling up. Seeded so a failure reproduces
+#[test]
+fn ok_moves_never_leave_duplicates() {
+    let puzzle_board =
+        SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+    for (seed, start_board) in [(7, SudokuBoard::new()), (42, puzzle_board)] {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut board = start_board;
+        let mut nr_ok_moves = 0;
+        for move_index in 0..NR_MOVES {
+            let cell_coordinate =
+                CellCoordinate::from_grid_index(rng.gen_range(0..81)).expect("Index is in range");
+            if rng.gen_ratio(1, 10) {
+                let _ = board.clear_value(cell_coordinate);
+                continue;
+            }
+            let value = rng.gen_range(1..=9);
+            let move_result = board.make_move(&SudokuMove {
+                cell_coordinate,
+                value,
+            });
+            if matches!(move_result, SudokuMoveResult::Ok { .. }) {
+                nr_ok_moves += 1;
+                assert!(
+                    board.is_valid(),
+                    "seed {} move {}: {} at {:?} was Ok but left duplicates\n{:?}",
+                    seed,
+                    move_index,
+                    value,
+                    cell_coordinate.to_rc(),
+                    board
+                );
+            }
+        }
+        // Guards against the loop silently testing nothing
+        assert!(
+            nr_ok_moves > 100,
+            "seed {} only made {} Ok moves",
+            seed,
+            nr_ok_moves
+        );
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use sudoku_rs::prelude::*;
+
+use common::PUZZLE_STR;
+
+#[test]
+fn prelude_covers_building_and_querying_a_board() {
+    let mut board = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+    let top_left = CellCoordinate {
+        sub_grid: PositionId {
+            row: Row::Upper,
+            column: Column::Left,
+        },
+        cell: PositionId {
+            row: Row::Upper,
+            column: Column::Left,
+        },
+    };
+    assert_eq!((0, 0), top_left.to_rc());
+    assert_eq!(Some(5), board.get_value(top_left));
+
+    let move_result = board.make_move(&SudokuMove {
+        cell_coordinate: top_left,
+        value: 1,
+    });
+    assert!(matches!(
+        move_result,
+        SudokuMoveResult::Rejected(CellError::GivenCell)
+    ));
+    assert!(matches!(
+        SudokuBoard::from_str_grid("123"),
+        Err(ParseError::WrongLength {
+            expected: 81,
+            found: 3
+        })
+    ));
+    assert!(SudokuBoard::generate_with_seed(Difficulty::Easy, 3).is_valid());
+}
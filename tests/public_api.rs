@@ -0,0 +1,36 @@
+mod common;
+
+use sudoku_rs::{CellCoordinate, SudokuBoard, SudokuMove, SudokuMoveResult};
+
+use common::PUZZLE_STR;
+
+#[test]
+fn board_is_built_and_solved_through_the_public_api() {
+    let mut board = SudokuBoard::from_str_grid(PUZZLE_STR).expect("Puzzle string is well formed");
+    let cell_coordinate = CellCoordinate::from_rc(0, 2).expect("Coordinate is in range");
+
+    let move_result = board.make_move(&SudokuMove {
+        cell_coordinate,
+        value: 4,
+    });
+    assert!(matches!(
+        move_result,
+        SudokuMoveResult::Ok { cell_coordinate: placed, value: 4 } if placed == cell_coordinate
+    ));
+    assert_eq!(Some(4), board.get_value(cell_coordinate));
+
+    assert!(board.solve());
+    assert!(board.is_complete());
+}
+
+#[test]
+fn csv_fixture_parses_into_the_same_puzzle() {
+    let board = SudokuBoard::from_csv(include_str!("fixtures/puzzle.csv"))
+        .expect("Fixture is a well formed CSV grid");
+    assert_eq!(PUZZLE_STR, board.to_str_grid());
+    assert_eq!(Some(5), board.get_rc(0, 0));
+    assert_eq!(None, board.get_rc(0, 2));
+    assert_eq!(Some(9), board.get_rc(8, 8));
+    let top_left = CellCoordinate::from_rc(0, 0).expect("Coordinate is in range");
+    assert!(board.is_given(top_left));
+}
@@ -0,0 +1,52 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+const SOLUTION_STR: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+fn run_solve(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_rs"))
+        .arg("--solve")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Binary was built for the integration tests");
+    child
+        .stdin
+        .take()
+        .expect("Stdin is piped")
+        .write_all(input.as_bytes())
+        .expect("Child reads stdin");
+    child.wait_with_output().expect("Child runs to completion")
+}
+
+#[test]
+fn solve_flag_prints_solution_of_fixture() {
+    let output = run_solve(include_str!("fixtures/puzzle.txt"));
+    assert!(output.status.success());
+    assert_eq!(SOLUTION_STR, String::from_utf8_lossy(&output.stdout).trim());
+}
+
+#[test]
+fn solve_flag_fails_on_malformed_input() {
+    let output = run_solve("53..7");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+}
+
+#[test]
+fn solve_flag_fails_on_unsolvable_puzzle() {
+    // A second 5 in the first row, well formed but with no solution
+    let unsolvable_puzzle = include_str!("fixtures/puzzle.txt").replacen('.', "5", 1);
+    let output = run_solve(&unsolvable_puzzle);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        "puzzle has no solution",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+}